@@ -0,0 +1,141 @@
+// src/alerts/mod.rs
+pub mod rules;
+pub mod notify;
+pub mod smtp;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::db::MongoDb;
+use crate::logging::LogStore;
+use rules::{AlertRule, Evaluation, RuleState};
+use smtp::SmtpConfig;
+use tracing::error;
+
+/// Alert rules and notification channels, from the `[alerts]` and `[smtp]` settings.
+/// A rule is only evaluated when its threshold is set.
+#[derive(Debug, Clone)]
+pub struct AlertConfig {
+    pub interval: Duration,
+    /// Span of recent traffic each rule looks at
+    pub window_minutes: i64,
+    /// How long a rule that keeps firing stays quiet before notifying again
+    pub cooldown: Duration,
+    pub error_rate_percent: Option<f64>,
+    pub p95_ms: Option<u64>,
+    pub mongo_health: bool,
+    /// Fire when a window has this many times the average traffic of the previous 24 hours
+    pub traffic_spike_factor: Option<f64>,
+    /// Traffic-based rules ignore windows with fewer requests than this
+    pub min_requests: u64,
+    pub webhook_url: Option<String>,
+    pub smtp: Option<SmtpConfig>,
+}
+
+impl AlertConfig {
+    /// Alerts only run when there is somewhere to send them
+    pub fn is_enabled(&self) -> bool {
+        self.webhook_url.is_some() || self.smtp.is_some()
+    }
+}
+
+/// Spawn the background job that evaluates alert rules and sends notifications
+pub fn spawn_alert_job(config: AlertConfig, logger_db: Arc<dyn LogStore>, mongo_db: Arc<MongoDb>) {
+    actix_web::rt::spawn(async move {
+        let mut states: HashMap<AlertRule, RuleState> = HashMap::new();
+        let mut ticker = actix_web::rt::time::interval(config.interval);
+
+        loop {
+            ticker.tick().await;
+
+            let evaluations = rules::evaluate(&config, &*logger_db, &mongo_db).await;
+            notify_changes(&config, &mut states, evaluations, Instant::now()).await;
+        }
+    });
+}
+
+/// Notify about every rule that changed state, or is still firing once its cooldown is over
+async fn notify_changes(
+    config: &AlertConfig,
+    states: &mut HashMap<AlertRule, RuleState>,
+    evaluations: Vec<Evaluation>,
+    now: Instant,
+) {
+    for evaluation in evaluations {
+        let state = states.entry(evaluation.rule).or_default();
+        let Some(transition) = state.update(evaluation.firing, now, config.cooldown) else {
+            continue;
+        };
+
+        let notification = notify::Notification::new(evaluation, transition);
+        for e in notify::send(config, &notification).await {
+            error!(error = %e, "Failed to send alert notification");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use serde_json::Value;
+
+    type Received = web::Data<Mutex<Vec<Value>>>;
+
+    async fn record(received: Received, body: web::Json<Value>) -> HttpResponse {
+        received.lock().unwrap().push(body.into_inner());
+        HttpResponse::NoContent().finish()
+    }
+
+    fn error_rate(firing: bool) -> Vec<Evaluation> {
+        vec![Evaluation { rule: AlertRule::ErrorRate, firing, summary: "12.5% of 80 requests failed".to_string() }]
+    }
+
+    #[actix_web::test]
+    async fn test_webhook_notifications() {
+        // Local stand-in for the webhook receiver, recording every payload
+        let received: Received = web::Data::new(Mutex::new(Vec::new()));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app_received = received.clone();
+        let server = HttpServer::new(move || App::new().app_data(app_received.clone()).route("/hook", web::post().to(record)))
+            .workers(1)
+            .disable_signals()
+            .listen(listener)
+            .unwrap()
+            .run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        let config = AlertConfig {
+            interval: Duration::from_secs(60),
+            window_minutes: 5,
+            cooldown: Duration::from_secs(30 * 60),
+            error_rate_percent: Some(5.0),
+            p95_ms: None,
+            mongo_health: false,
+            traffic_spike_factor: None,
+            min_requests: 20,
+            webhook_url: Some(format!("http://127.0.0.1:{}/hook", port)),
+            smtp: None,
+        };
+        let mut states = HashMap::new();
+        let start = Instant::now();
+
+        notify_changes(&config, &mut states, error_rate(true), start).await;
+        // Still firing within the cooldown, so nothing is sent
+        notify_changes(&config, &mut states, error_rate(true), start + Duration::from_secs(60)).await;
+        notify_changes(&config, &mut states, error_rate(true), start + config.cooldown).await;
+        notify_changes(&config, &mut states, error_rate(false), start + config.cooldown + Duration::from_secs(60)).await;
+        handle.stop(true).await;
+
+        let received = received.lock().unwrap();
+        let statuses: Vec<&str> = received.iter().map(|payload| payload["status"].as_str().unwrap()).collect();
+        assert_eq!(statuses, ["firing", "repeat", "resolved"]);
+        assert_eq!(received[0]["rule"], "error_rate");
+        assert_eq!(received[0]["title"], "🔥 FIRING: High error rate");
+        assert_eq!(received[0]["summary"], "12.5% of 80 requests failed");
+        assert_eq!(received[0]["text"], "🔥 FIRING: High error rate — 12.5% of 80 requests failed");
+    }
+}
@@ -0,0 +1,83 @@
+// src/alerts/notify.rs
+use chrono::Utc;
+use serde::Serialize;
+use super::AlertConfig;
+use super::rules::{AlertRule, Evaluation, Transition};
+use super::smtp;
+
+/// A message about a rule changing state, sent to every configured channel
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub rule: Option<AlertRule>, // None for test notifications
+    pub status: Option<Transition>,
+    pub title: String,
+    pub summary: String,
+    pub timestamp: String,
+    /// Chat-style webhooks (Slack, Mattermost, Discord-compatible) display this field
+    pub text: String,
+}
+
+impl Notification {
+    pub fn new(evaluation: Evaluation, transition: Transition) -> Self {
+        let prefix = match transition {
+            Transition::Firing => "🔥 FIRING",
+            Transition::Repeat => "🔥 STILL FIRING",
+            Transition::Resolved => "✅ RESOLVED",
+        };
+        let title = format!("{}: {}", prefix, evaluation.rule.title());
+
+        Notification {
+            rule: Some(evaluation.rule),
+            status: Some(transition),
+            text: format!("{} — {}", title, evaluation.summary),
+            title,
+            summary: evaluation.summary,
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn test() -> Self {
+        let title = "Test notification".to_string();
+        let summary = "Alert notifications are configured correctly".to_string();
+        Notification {
+            rule: None,
+            status: None,
+            text: format!("{} — {}", title, summary),
+            title,
+            summary,
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Deliver to the webhook and SMTP, whichever are configured; returns one error per failed channel
+pub async fn send(config: &AlertConfig, notification: &Notification) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if let Some(url) = &config.webhook_url
+        && let Err(e) = send_webhook(url, notification).await
+    {
+        errors.push(format!("webhook: {}", e));
+    }
+
+    if let Some(smtp_config) = &config.smtp {
+        let subject = format!("[wizards-portfolio] {}", notification.title);
+        let body = format!("{}\n\n{}\n", notification.summary, notification.timestamp);
+        if let Err(e) = smtp::send_mail(smtp_config, &subject, &body).await {
+            errors.push(format!("smtp: {}", e));
+        }
+    }
+
+    errors
+}
+
+async fn send_webhook(url: &str, notification: &Notification) -> Result<(), reqwest::Error> {
+    reqwest::Client::new()
+        .post(url)
+        .timeout(std::time::Duration::from_secs(10))
+        .json(notification)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
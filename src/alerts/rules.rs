@@ -0,0 +1,165 @@
+// src/alerts/rules.rs
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde::Serialize;
+use crate::db::{verify_connection, MongoDb};
+use crate::logging::LogStore;
+use super::AlertConfig;
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertRule {
+    ErrorRate,
+    Latency,
+    MongoHealth,
+    TrafficSpike,
+}
+
+impl AlertRule {
+    pub fn title(&self) -> &'static str {
+        match self {
+            AlertRule::ErrorRate => "High error rate",
+            AlertRule::Latency => "High p95 latency",
+            AlertRule::MongoHealth => "MongoDB unreachable",
+            AlertRule::TrafficSpike => "Traffic spike",
+        }
+    }
+}
+
+/// Result of checking one rule
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub rule: AlertRule,
+    pub firing: bool,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Firing,
+    /// Still firing after the cooldown elapsed
+    Repeat,
+    Resolved,
+}
+
+/// Per-rule memory between evaluations, so only changes (and reminders after the cooldown) notify
+#[derive(Debug, Clone, Default)]
+pub struct RuleState {
+    firing: bool,
+    last_notified: Option<Instant>,
+}
+
+impl RuleState {
+    pub fn update(&mut self, firing: bool, now: Instant, cooldown: Duration) -> Option<Transition> {
+        let transition = match (self.firing, firing) {
+            (false, true) => Some(Transition::Firing),
+            (true, false) => Some(Transition::Resolved),
+            (true, true) if self.last_notified.is_none_or(|last| now.duration_since(last) >= cooldown) => {
+                Some(Transition::Repeat)
+            }
+            _ => None,
+        };
+
+        self.firing = firing;
+        if transition.is_some() {
+            self.last_notified = Some(now);
+        }
+        transition
+    }
+}
+
+/// Check every configured rule. Log-based rules are skipped while MongoDB can't be queried,
+/// so their state carries over until it can.
+pub async fn evaluate(config: &AlertConfig, logger_db: &dyn LogStore, mongo_db: &MongoDb) -> Vec<Evaluation> {
+    let mut evaluations = Vec::new();
+
+    let ping = verify_connection(mongo_db).await;
+    if config.mongo_health {
+        evaluations.push(Evaluation {
+            rule: AlertRule::MongoHealth,
+            firing: ping.is_err(),
+            summary: match &ping {
+                Ok(()) => "MongoDB is answering pings again".to_string(),
+                Err(e) => format!("MongoDB health check failed: {}", e),
+            },
+        });
+    }
+    if ping.is_err() {
+        return evaluations;
+    }
+
+    let window = chrono::Duration::minutes(config.window_minutes);
+    let since = Utc::now() - window;
+    let summary = match logger_db.get_traffic_summary(since).await {
+        Ok(summary) => summary,
+        Err(e) => {
+            error!(error = %e, "Failed to evaluate alert rules");
+            return evaluations;
+        }
+    };
+    let enough_traffic = summary.requests >= config.min_requests;
+
+    if let Some(threshold) = config.error_rate_percent {
+        let rate = summary.error_rate_percent();
+        evaluations.push(Evaluation {
+            rule: AlertRule::ErrorRate,
+            firing: enough_traffic && rate > threshold,
+            summary: format!(
+                "{:.1}% of {} requests failed with 5xx in the last {} minutes (threshold {}%)",
+                rate, summary.requests, config.window_minutes, threshold
+            ),
+        });
+    }
+
+    if let Some(threshold) = config.p95_ms {
+        evaluations.push(Evaluation {
+            rule: AlertRule::Latency,
+            firing: enough_traffic && summary.latency.p95 > threshold as f64,
+            summary: format!(
+                "p95 latency was {} ms over the last {} minutes (threshold {} ms)",
+                summary.latency.p95, config.window_minutes, threshold
+            ),
+        });
+    }
+
+    if let Some(factor) = config.traffic_spike_factor {
+        match logger_db.count_requests_between(since - chrono::Duration::hours(24), since).await {
+            Ok(previous_day) => {
+                let windows_per_day = (24 * 60) as f64 / config.window_minutes.max(1) as f64;
+                let baseline = previous_day as f64 / windows_per_day;
+                evaluations.push(Evaluation {
+                    rule: AlertRule::TrafficSpike,
+                    firing: enough_traffic && summary.requests as f64 > factor * baseline.max(1.0),
+                    summary: format!(
+                        "{} requests in the last {} minutes, against an average of {:.1} over the previous day (spike factor {})",
+                        summary.requests, config.window_minutes, baseline, factor
+                    ),
+                });
+            }
+            Err(e) => error!(error = %e, "Failed to evaluate traffic spike rule"),
+        }
+    }
+
+    evaluations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_state_transitions() {
+        let cooldown = Duration::from_secs(30 * 60);
+        let start = Instant::now();
+        let mut state = RuleState::default();
+
+        assert_eq!(state.update(false, start, cooldown), None);
+        assert_eq!(state.update(true, start, cooldown), Some(Transition::Firing));
+        assert_eq!(state.update(true, start + Duration::from_secs(60), cooldown), None);
+        assert_eq!(state.update(true, start + cooldown, cooldown), Some(Transition::Repeat));
+        assert_eq!(state.update(false, start + cooldown + Duration::from_secs(60), cooldown), Some(Transition::Resolved));
+        assert_eq!(state.update(false, start + cooldown * 2, cooldown), None);
+    }
+}
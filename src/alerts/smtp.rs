@@ -0,0 +1,149 @@
+// src/alerts/smtp.rs
+use std::str::FromStr;
+use std::time::Duration;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+type SmtpError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain text, only sensible for a relay on localhost or a test server
+    None,
+    /// Upgrade with STARTTLS (usually port 587)
+    StartTls,
+    /// TLS from the first byte (usually port 465)
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::None => 25,
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+        }
+    }
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "plain" => Ok(SmtpSecurity::None),
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" | "ssl" => Ok(SmtpSecurity::Tls),
+            other => Err(format!("unknown SMTP security '{}' (expected none, starttls or tls)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Send a plain-text mail to every recipient in `config.to`
+pub async fn send_mail(config: &SmtpConfig, subject: &str, body: &str) -> Result<(), SmtpError> {
+    let mut message = Message::builder()
+        .from(config.from.parse()?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &config.to {
+        message = message.to(to.parse()?);
+    }
+    let message = message.body(body.to_string())?;
+
+    let transport = match config.security {
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+    };
+    let mut transport = transport.port(config.port).timeout(Some(Duration::from_secs(30)));
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport.build().send(message).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Minimal SMTP stand-in that accepts one message and returns everything the client sent
+    async fn fake_server(listener: TcpListener) -> String {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = BufReader::new(socket);
+        let mut received = String::new();
+        let mut in_data = false;
+
+        socket.get_mut().write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+        loop {
+            let mut line = String::new();
+            if socket.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            received.push_str(&line);
+
+            let reply: &[u8] = if in_data {
+                if line != ".\r\n" {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line.starts_with("EHLO") {
+                b"250-localhost\r\n250 AUTH PLAIN\r\n"
+            } else if line.starts_with("AUTH") {
+                b"235 ok\r\n"
+            } else if line.starts_with("DATA") {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line.starts_with("QUIT") {
+                socket.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 ok\r\n"
+            };
+            socket.get_mut().write_all(reply).await.unwrap();
+        }
+
+        received
+    }
+
+    #[actix_web::test]
+    async fn test_send_mail() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = actix_web::rt::spawn(fake_server(listener));
+
+        let config = SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: Some("alerts".to_string()),
+            password: Some("secret".to_string()),
+            from: "alerts@example.com".to_string(),
+            to: vec!["ops@example.com".to_string(), "me@example.com".to_string()],
+        };
+        send_mail(&config, "Site down", "It broke\n.hidden line").await.unwrap();
+
+        let received = server.await.unwrap();
+        assert!(received.contains("MAIL FROM:<alerts@example.com>\r\n"));
+        assert!(received.contains("RCPT TO:<me@example.com>\r\n"));
+        assert!(received.contains("Subject: Site down\r\n"));
+        assert!(received.contains("\r\n..hidden line\r\n.\r\n"));
+        assert!(received.ends_with("QUIT\r\n"));
+    }
+}
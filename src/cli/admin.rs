@@ -0,0 +1,99 @@
+// src/cli/admin.rs
+use crate::cli::prompt;
+use crate::db::UserRepository;
+
+/// Where the commands get a new password from: `cli::prompt_new_password`, or a fixed one in tests
+pub type PasswordSource = fn() -> Result<String, Box<dyn std::error::Error>>;
+
+/// Create the single admin user; refuses when one already exists
+pub async fn create_admin(
+    users: &dyn UserRepository,
+    username: Option<String>,
+    email: Option<String>,
+    new_password: PasswordSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if users.admin_exists().await? {
+        return Err("an admin user already exists; use reset-password to change its password".into());
+    }
+
+    let username = match username {
+        Some(username) => username,
+        None => prompt("Username", Some("admin"))?,
+    };
+    let email = match email {
+        Some(email) => email,
+        None => prompt("Email", None)?,
+    };
+    if username.trim().is_empty() {
+        return Err("the username must not be empty".into());
+    }
+    if !email.contains('@') {
+        return Err(format!("'{}' is not an email address", email).into());
+    }
+    let password = new_password()?;
+
+    let user = users.initialize_admin(username.trim(), &password, email.trim()).await?;
+    println!("Created admin user '{}'", user.username);
+    Ok(())
+}
+
+pub async fn reset_password(
+    users: &dyn UserRepository,
+    username: Option<String>,
+    new_password: PasswordSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = match username {
+        Some(username) => username,
+        None => match users.get_admin_user().await? {
+            Some(admin) => admin.username,
+            None => return Err("there is no admin user yet; use create-admin".into()),
+        },
+    };
+    println!("Setting a new password for '{}'", username);
+    let password = new_password()?;
+
+    if !users.set_password(&username, &password).await? {
+        return Err(format!("no user named '{}'", username).into());
+    }
+    println!("Password updated");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repository::MemoryUserRepository;
+
+    fn first_password() -> Result<String, Box<dyn std::error::Error>> {
+        Ok("correct horse".to_string())
+    }
+
+    fn second_password() -> Result<String, Box<dyn std::error::Error>> {
+        Ok("battery staple".to_string())
+    }
+
+    #[actix_web::test]
+    async fn test_create_admin_and_reset_password() {
+        let users = MemoryUserRepository::new();
+        let username = Some("admin".to_string());
+        let email = Some("admin@example.com".to_string());
+
+        create_admin(&users, username.clone(), Some("not an email".to_string()), first_password).await.unwrap_err();
+        assert!(!users.admin_exists().await.unwrap());
+
+        create_admin(&users, username.clone(), email.clone(), first_password).await.unwrap();
+        let admin = users.get_admin_user().await.unwrap().unwrap();
+        assert_eq!(admin.email, "admin@example.com");
+        assert!(bcrypt::verify("correct horse", &admin.password_hash).unwrap());
+
+        // There is only ever one admin
+        create_admin(&users, Some("other".to_string()), email, first_password).await.unwrap_err();
+
+        reset_password(&users, None, second_password).await.unwrap();
+        let admin = users.get_admin_user().await.unwrap().unwrap();
+        assert!(bcrypt::verify("battery staple", &admin.password_hash).unwrap());
+
+        let error = reset_password(&users, Some("nobody".to_string()), second_password).await.unwrap_err();
+        assert_eq!(error.to_string(), "no user named 'nobody'");
+    }
+}
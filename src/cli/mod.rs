@@ -0,0 +1,179 @@
+// src/cli/mod.rs
+pub mod admin;
+pub mod posts;
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::{NaiveDate, TimeZone, Utc};
+use clap::{Parser, Subcommand};
+use crate::config::{app_config, LOG_STORE};
+use crate::db::{self, connect_with_retry, MongoDb};
+use crate::logging::store::{open_log_store, LogStoreKind};
+
+/// Portfolio web server and the admin tasks that go with it
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// TOML config file (default: $CONFIG_FILE, then ./config.toml when present)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective configuration with secrets redacted, then exit
+    #[arg(long, global = true)]
+    pub print_config: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the web server (the default)
+    Serve,
+    /// Create the admin user, prompting for anything not given
+    CreateAdmin {
+        #[arg(long)]
+        username: Option<String>,
+        #[arg(long)]
+        email: Option<String>,
+    },
+    /// Set a new password, prompting for it
+    ResetPassword {
+        /// Defaults to the admin user
+        #[arg(long)]
+        username: Option<String>,
+    },
+    /// Apply pending database migrations
+    Migrate {
+        /// List applied and pending migrations instead
+        #[arg(long)]
+        status: bool,
+    },
+    /// Write every post as JSON
+    ExportPosts {
+        /// Defaults to stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Insert posts from an `export-posts` file, replacing posts with the same ID
+    ImportPosts {
+        input: PathBuf,
+    },
+    /// Delete request, click and performance logs from before a date (UTC)
+    PurgeLogs {
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        before: NaiveDate,
+        /// Don't ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// Run an admin command; `serve` is handled by `main`
+pub async fn run(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    // Asked before connecting, so declining doesn't wait on MongoDB
+    if let Command::PurgeLogs { before, yes: false } = &command
+        && !confirm(&format!("Delete all request, click and performance logs before {}?", before))?
+    {
+        println!("Nothing deleted");
+        return Ok(());
+    }
+
+    match command {
+        Command::Serve => unreachable!("serve is run by main"),
+        Command::CreateAdmin { username, email } => {
+            let mongodb = connect().await?;
+            admin::create_admin(&db::repository::MongoUserRepository::new(&mongodb.database), username, email, prompt_new_password).await
+        }
+        Command::ResetPassword { username } => {
+            let mongodb = connect().await?;
+            admin::reset_password(&db::repository::MongoUserRepository::new(&mongodb.database), username, prompt_new_password).await
+        }
+        Command::Migrate { status } => {
+            let mongodb = connect().await?;
+            migrate(&mongodb, status).await
+        }
+        Command::ExportPosts { output } => {
+            let mongodb = connect().await?;
+            posts::export_posts(&db::repository::MongoPostRepository::new(&mongodb.database), output).await
+        }
+        Command::ImportPosts { input } => {
+            let mongodb = connect().await?;
+            posts::import_posts(&db::repository::MongoPostRepository::new(&mongodb.database), &input).await
+        }
+        Command::PurgeLogs { before, .. } => purge_logs(before).await,
+    }
+}
+
+/// Connect to MongoDB, only for the commands that use it
+async fn connect() -> Result<Arc<MongoDb>, Box<dyn std::error::Error>> {
+    Ok(Arc::new(connect_with_retry(&app_config().mongodb).await?))
+}
+
+async fn migrate(mongodb: &MongoDb, status: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if status {
+        for applied in db::migrations::applied_migrations(&mongodb.database).await? {
+            println!("applied  {:>3}  {}  ({})", applied.version, applied.name, applied.applied_at);
+        }
+        for pending in db::migrations::pending_migrations(&mongodb.database).await? {
+            println!("pending  {:>3}  {}", pending.version, pending.name);
+        }
+        return Ok(());
+    }
+
+    let applied = db::migrations::run_pending(&mongodb.database).await?;
+    match applied.len() {
+        0 => println!("Database is up to date"),
+        count => println!("Applied {} migrations: {:?}", count, applied),
+    }
+    Ok(())
+}
+
+async fn purge_logs(before: NaiveDate) -> Result<(), Box<dyn std::error::Error>> {
+    let before = Utc.from_utc_datetime(&before.and_hms_opt(0, 0, 0).unwrap_or_default());
+    let mongodb = match *LOG_STORE {
+        LogStoreKind::Mongo => Some(connect().await?),
+        LogStoreKind::Memory | LogStoreKind::File(_) => None,
+    };
+    let log_store = open_log_store(&LOG_STORE, mongodb.as_ref())?;
+    let (requests, clicks, performance) = log_store.purge_before(before).await?;
+    println!("Deleted {} request logs, {} click logs and {} performance samples", requests, clicks, performance);
+    Ok(())
+}
+
+/// Ask for a line of input, falling back to `default` when the answer is empty
+pub fn prompt(label: &str, default: Option<&str>) -> io::Result<String> {
+    match default {
+        Some(default) => print!("{} [{}]: ", label, default),
+        None => print!("{}: ", label),
+    }
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no input"));
+    }
+    let answer = line.trim();
+    Ok(match (answer.is_empty(), default) {
+        (true, Some(default)) => default.to_string(),
+        _ => answer.to_string(),
+    })
+}
+
+fn confirm(question: &str) -> io::Result<bool> {
+    let answer = prompt(&format!("{} [y/N]", question), None)?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Read a new password twice without echoing it
+pub fn prompt_new_password() -> Result<String, Box<dyn std::error::Error>> {
+    let password = rpassword::prompt_password("Password: ")?;
+    if password.chars().count() < 8 {
+        return Err("the password must be at least 8 characters".into());
+    }
+    if rpassword::prompt_password("Repeat password: ")? != password {
+        return Err("the passwords don't match".into());
+    }
+    Ok(password)
+}
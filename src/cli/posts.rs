@@ -0,0 +1,84 @@
+// src/cli/posts.rs
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::db::PostRepository;
+use crate::types::Post;
+
+/// Write every post, newest first, as a JSON array (MongoDB extended JSON for IDs and dates)
+pub async fn export_posts(posts: &dyn PostRepository, output: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let all = posts.list_posts(None, 0, 0).await?;
+
+    let mut writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    serde_json::to_writer_pretty(&mut writer, &all)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    if let Some(path) = output {
+        println!("Exported {} posts to {}", all.len(), path.display());
+    }
+    Ok(())
+}
+
+/// Load an `export_posts` file, keeping IDs so importing twice replaces rather than duplicates
+pub async fn import_posts(posts: &dyn PostRepository, input: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let imported: Vec<Post> = serde_json::from_reader(BufReader::new(File::open(input)?))
+        .map_err(|e| format!("{} is not a post export: {}", input.display(), e))?;
+
+    for post in &imported {
+        posts.save_post(post).await?;
+    }
+    println!("Imported {} posts from {}", imported.len(), input.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+    use crate::db::repository::MemoryPostRepository;
+    use crate::types::PostStatus;
+
+    #[actix_web::test]
+    async fn test_export_import_round_trip() {
+        let created_at = BsonDateTime::from_millis(1_767_225_600_123);
+        let post = Post {
+            id: Some(ObjectId::new()),
+            title: "Hello World".to_string(),
+            slug: "hello-world".to_string(),
+            content: "Content".to_string(),
+            excerpt: None,
+            status: PostStatus::Published,
+            author_id: ObjectId::new(),
+            created_at,
+            updated_at: created_at,
+            published_at: Some(created_at),
+            tags: vec!["rust".to_string()],
+            views: 7,
+        };
+        let source = MemoryPostRepository::new();
+        source.save_post(&post).await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("posts-{}.json", ObjectId::new()));
+        export_posts(&source, Some(path.clone())).await.unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("\"$oid\""));
+
+        // Importing twice replaces the post instead of duplicating it
+        let target = MemoryPostRepository::new();
+        import_posts(&target, &path).await.unwrap();
+        import_posts(&target, &path).await.unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let imported = target.list_posts(None, 0, 0).await.unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].id, post.id);
+        assert_eq!(imported[0].author_id, post.author_id);
+        assert_eq!(imported[0].created_at, created_at);
+        assert_eq!(imported[0].published_at, Some(created_at));
+        assert_eq!(imported[0].status, PostStatus::Published);
+        assert_eq!(imported[0].views, 7);
+    }
+}
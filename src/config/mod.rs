@@ -0,0 +1,91 @@
+pub mod app;
+
+pub use app::{AppConfig, ConfigError};
+
+use std::sync::OnceLock;
+use tera::Tera;
+use lazy_static::lazy_static;
+use crate::logging::privacy::PrivacyMode;
+use crate::logging::proxy::TrustedProxies;
+use crate::logging::telemetry::{LogFormat, OtlpConfig};
+use crate::logging::store::LogStoreKind;
+use crate::alerts::AlertConfig;
+
+/// The Tera instance, rendering each template inside its own span
+pub struct Templates(Tera);
+
+impl Templates {
+    pub fn render(&self, template_name: &str, context: &tera::Context) -> tera::Result<String> {
+        let _span = tracing::debug_span!("render_template", template = template_name).entered();
+        self.0.render(template_name, context)
+    }
+
+    /// Whether `template_name` was loaded
+    pub fn contains(&self, template_name: &str) -> bool {
+        self.0.get_template_names().any(|name| name == template_name)
+    }
+}
+
+static APP_CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+/// Install the configuration validated at startup; the settings below are read from it
+pub fn init(config: AppConfig) {
+    if APP_CONFIG.set(config).is_err() {
+        panic!("configuration initialized twice");
+    }
+}
+
+/// The configuration given to `init`, or loaded from the default sources when there was none (tests)
+pub fn app_config() -> &'static AppConfig {
+    APP_CONFIG.get_or_init(|| AppConfig::load(None).unwrap_or_else(|e| panic!("{}", e)))
+}
+
+lazy_static! {
+    pub static ref TEMPLATES: Templates = {
+        Templates(Tera::new("src/templates/**/*").expect("Template loading failed"))
+    };
+
+    pub static ref IS_DEV: bool = app_config().is_dev();
+
+    /// How often the background job materializes `daily_stats` rollups
+    pub static ref ROLLUP_INTERVAL_SECS: u64 = app_config().logging.rollup_interval_secs;
+
+    /// How often the system sampler writes a `performance_logs` entry
+    pub static ref PERFORMANCE_SAMPLE_SECS: u64 = app_config().logging.performance_sample_secs;
+
+    /// Bearer token required to scrape `/metrics` on the main port
+    pub static ref METRICS_TOKEN: Option<String> = app_config().metrics.token.clone();
+
+    /// Bearer token for the admin endpoints; they are disabled without one
+    pub static ref ADMIN_TOKEN: Option<String> = app_config().admin.token.clone();
+
+    /// When set, `/metrics` is served only on this separate admin port
+    pub static ref METRICS_PORT: Option<u16> = app_config().metrics.port;
+
+    /// Path to a MaxMind-format (.mmdb) city database; GeoIP enrichment is skipped when unset
+    pub static ref GEOIP_DATABASE: Option<String> = app_config().privacy.geoip_database.clone();
+
+    /// Keep the full `Referer` URL; by default only the normalized host is stored
+    pub static ref STORE_FULL_REFERRER: bool = app_config().privacy.store_full_referrer;
+
+    /// How client IPs are stored: `off`, `truncate` or `hash`
+    pub static ref PRIVACY_MODE: PrivacyMode = app_config().privacy.mode;
+
+    /// Skip logging for requests carrying `DNT: 1` or `Sec-GPC: 1`
+    pub static ref HONOR_DO_NOT_TRACK: bool = app_config().privacy.honor_do_not_track;
+
+    /// Reverse proxies allowed to report the client IP via forwarding headers
+    pub static ref TRUSTED_PROXIES: TrustedProxies = app_config().privacy.trusted_proxies.clone();
+
+    /// Diagnostics output: `text` or `json`
+    pub static ref LOG_FORMAT: LogFormat = app_config().logging.format;
+
+    /// Backend for analytics logs: `mongo` (default), `memory` or `file`
+    pub static ref LOG_STORE: LogStoreKind = app_config().logging.store.clone();
+
+    /// OTLP trace export, disabled unless an `OTEL_EXPORTER_OTLP_*ENDPOINT` is set
+    pub static ref OTLP: Option<OtlpConfig> = app_config().otlp.clone();
+
+    /// Alert rules and notification channels, see `AlertConfig`
+    pub static ref ALERTS: AlertConfig = app_config().alerts.clone();
+}
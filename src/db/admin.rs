@@ -1,14 +1,17 @@
 use mongodb::{Database, bson::{doc, oid::ObjectId, DateTime as BsonDateTime}};
 use crate::types::User;
 use bcrypt::{verify, hash, DEFAULT_COST};
+use tracing::instrument;
 
 /// Get the admin user (the first user in the system)
+#[instrument(level = "debug", skip_all)]
 pub async fn get_admin_user(db: &Database) -> Result<Option<User>, mongodb::error::Error> {
     let collection = db.collection::<User>("users");
     collection.find_one(doc! {}).await
 }
 
 /// Verify admin password
+#[instrument(level = "debug", skip_all)]
 pub async fn verify_admin_password(
     db: &Database,
     username: &str,
@@ -33,6 +36,7 @@ pub async fn verify_admin_password(
 }
 
 /// Update last login time
+#[instrument(level = "debug", skip_all)]
 pub async fn update_last_login(
     db: &Database,
     user_id: ObjectId,
@@ -47,7 +51,26 @@ pub async fn update_last_login(
     Ok(())
 }
 
+/// Replace a user's password; returns whether the user exists
+#[instrument(level = "debug", skip_all)]
+pub async fn set_password(
+    db: &Database,
+    username: &str,
+    password: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let password_hash = hash(password, DEFAULT_COST)?;
+    let collection = db.collection::<User>("users");
+    
+    let result = collection.update_one(
+        doc! { "username": username },
+        doc! { "$set": { "password_hash": password_hash } },
+    ).await?;
+    
+    Ok(result.matched_count > 0)
+}
+
 /// Initialize admin user (run this once during setup)
+#[instrument(level = "debug", skip_all)]
 pub async fn initialize_admin(
     db: &Database,
     username: &str,
@@ -77,6 +100,7 @@ pub async fn initialize_admin(
 }
 
 /// Check if any admin exists
+#[instrument(level = "debug", skip_all)]
 pub async fn admin_exists(db: &Database) -> Result<bool, mongodb::error::Error> {
     let collection = db.collection::<User>("users");
     let count = collection.count_documents(doc! {}).await?;
@@ -1,16 +1,18 @@
 use actix_web::web::get;
 use mongodb::{Client, Database as MongoDatabase};
-use std::env;
+use mongodb::event::EventHandler;
+use mongodb::options::ClientOptions;
 use std::time::Duration;
 use tokio::time::sleep;
 use crate::types;
+use crate::config::app::MongoConfig;
+use crate::logging::metrics;
+use tracing::{info, instrument, warn};
 
 pub type Database = MongoDatabase;
 
-pub fn get_database(client: &Client) -> Database {
-    let db_name = env::var("MONGODB_DATABASE")
-        .unwrap_or_else(|_| "wizards_portfolio".to_string());
-    client.database(&db_name)
+pub fn get_database(client: &Client, config: &MongoConfig) -> Database {
+    client.database(&config.database)
 }
 
 pub struct MongoDb {
@@ -19,11 +21,11 @@ pub struct MongoDb {
 }
 
 impl MongoDb {
-    pub async fn new() -> mongodb::error::Result<Self> {
-        let uri = env::var("MONGODB_URI")
-            .unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
-        let client = Client::with_uri_str(uri).await?;
-        let database = get_database(&client);
+    pub async fn new(config: &MongoConfig) -> mongodb::error::Result<Self> {
+        let mut options = ClientOptions::parse(&config.uri).await?;
+        options.command_event_handler = Some(EventHandler::callback(metrics::observe_mongo_command));
+        let client = Client::with_options(options)?;
+        let database = get_database(&client, config);
         Ok(Self { client, database })
     }
 
@@ -33,17 +35,18 @@ impl MongoDb {
 }
 
 /// Connect to MongoDB with exponential backoff retry logic
-pub async fn connect_with_retry() -> Result<MongoDb, String> {
+#[instrument(skip_all)]
+pub async fn connect_with_retry(config: &MongoConfig) -> Result<MongoDb, String> {
     let max_retries = 10;
     let mut retry_count = 0;
     let mut backoff_ms = 500;
 
     loop {
-        match MongoDb::new().await {
+        match MongoDb::new(config).await {
             Ok(db) => {
                 // Verify connection with a ping
                 if let Err(e) = verify_connection(&db).await {
-                    eprintln!("⚠️  MongoDB ping failed: {}", e);
+                    warn!(error = %e, "MongoDB ping failed");
                     retry_count += 1;
                     if retry_count >= max_retries {
                         return Err(format!(
@@ -51,10 +54,7 @@ pub async fn connect_with_retry() -> Result<MongoDb, String> {
                             max_retries, e
                         ));
                     }
-                    println!(
-                        "🔄 Retrying MongoDB connection in {}ms... (Attempt {}/{})",
-                        backoff_ms, retry_count + 1, max_retries
-                    );
+                    info!(backoff_ms, attempt = retry_count + 1, max_retries, "Retrying MongoDB connection");
                     sleep(Duration::from_millis(backoff_ms)).await;
                     backoff_ms = (backoff_ms * 2).min(5000); // Max 5 seconds between retries
                     continue;
@@ -64,7 +64,7 @@ pub async fn connect_with_retry() -> Result<MongoDb, String> {
                 return Ok(db);
             }
             Err(e) => {
-                eprintln!("⚠️  MongoDB connection failed: {}", e);
+                warn!(error = %e, "MongoDB connection failed");
                 retry_count += 1;
                 if retry_count >= max_retries {
                     return Err(format!(
@@ -72,10 +72,7 @@ pub async fn connect_with_retry() -> Result<MongoDb, String> {
                         max_retries, e
                     ));
                 }
-                println!(
-                    "🔄 Retrying MongoDB connection in {}ms... (Attempt {}/{})",
-                    backoff_ms, retry_count + 1, max_retries
-                );
+                info!(backoff_ms, attempt = retry_count + 1, max_retries, "Retrying MongoDB connection");
                 sleep(Duration::from_millis(backoff_ms)).await;
                 backoff_ms = (backoff_ms * 2).min(5000); // Max 5 seconds between retries
             }
@@ -84,6 +81,7 @@ pub async fn connect_with_retry() -> Result<MongoDb, String> {
 }
 
 /// Verify MongoDB connection with a ping command
+#[instrument(level = "debug", skip_all)]
 pub async fn verify_connection(db: &MongoDb) -> Result<(), String> {
     match db.database.run_command(
         mongodb::bson::doc! { "ping": 1 },
@@ -91,4 +89,42 @@ pub async fn verify_connection(db: &MongoDb) -> Result<(), String> {
         Ok(_) => Ok(()),
         Err(e) => Err(format!("Ping failed: {}", e)),
     }
-}
\ No newline at end of file
+}
+/// Oldest MongoDB release the log store's aggregations run on (`$percentile` is new in 7.0)
+pub const MIN_SERVER_VERSION: (u32, u32) = (7, 0);
+
+/// Fail unless the server, as reported by `buildInfo`, is at least `MIN_SERVER_VERSION`
+#[instrument(level = "debug", skip_all)]
+pub async fn check_server_version(db: &MongoDb) -> Result<(), String> {
+    let info = db.database.run_command(mongodb::bson::doc! { "buildInfo": 1 })
+        .await
+        .map_err(|e| format!("buildInfo failed: {}", e))?;
+    let version = info.get_str("version").map_err(|_| "buildInfo reported no version".to_string())?;
+
+    match parse_version(version) {
+        Some(found) if found >= MIN_SERVER_VERSION => Ok(()),
+        _ => Err(format!(
+            "MongoDB {} is too old for the mongo log store, it needs {}.{} or newer",
+            version, MIN_SERVER_VERSION.0, MIN_SERVER_VERSION.1,
+        )),
+    }
+}
+
+/// Major and minor version of a release like "7.0.14"
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("7.0.14"), Some((7, 0)));
+        assert_eq!(parse_version("8.2.0-rc1"), Some((8, 2)));
+        assert!(parse_version("6.0.20").is_some_and(|version| version < MIN_SERVER_VERSION));
+        assert_eq!(parse_version("unknown"), None);
+    }
+}
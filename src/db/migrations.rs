@@ -0,0 +1,175 @@
+use chrono::Utc;
+use futures::future::LocalBoxFuture;
+use futures::stream::TryStreamExt;
+use mongodb::{Database, IndexModel, bson::{doc, Bson, Document, DateTime as BsonDateTime}, options::IndexOptions};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+
+/// Collection recording which migrations have been applied
+const MIGRATIONS_COLLECTION: &str = "_migrations";
+
+type MigrationResult = Result<(), Box<dyn std::error::Error>>;
+
+/// A schema or data change, applied once per database in `version` order
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    run: for<'a> fn(&'a Database) -> LocalBoxFuture<'a, MigrationResult>,
+}
+
+/// Every migration, oldest first. Never edit or reorder one that has shipped; add a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_post_and_user_indexes",
+        run: |db| Box::pin(create_post_and_user_indexes(db)),
+    },
+    Migration {
+        version: 2,
+        name: "create_log_indexes",
+        run: |db| Box::pin(create_log_indexes(db)),
+    },
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    #[serde(rename = "_id")]
+    pub version: u32,
+    pub name: String,
+    pub applied_at: BsonDateTime,
+    pub duration_ms: i64,
+}
+
+async fn create_index(db: &Database, collection: &str, keys: Document, unique: bool) -> MigrationResult {
+    let index = IndexModel::builder()
+        .keys(keys)
+        .options(IndexOptions::builder().unique(unique).build())
+        .build();
+    let result = db.collection::<Document>(collection).create_index(index).await?;
+    info!(collection, index = %result.index_name, "Index created");
+    Ok(())
+}
+
+/// Fail with the values that occur more than once, rather than with MongoDB's duplicate key error
+/// for the first of them, when `field` is about to get a unique index
+async fn ensure_unique(db: &Database, collection: &str, field: &str) -> MigrationResult {
+    let pipeline = vec![
+        doc! { "$group": { "_id": format!("${}", field), "count": { "$sum": 1 } } },
+        doc! { "$match": { "count": { "$gt": 1 } } },
+        doc! { "$sort": { "_id": 1 } },
+    ];
+    let duplicates: Vec<Document> = db.collection::<Document>(collection).aggregate(pipeline).await?.try_collect().await?;
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+
+    let values: Vec<String> = duplicates
+        .iter()
+        .map(|duplicate| match (duplicate.get("_id"), duplicate.get("count")) {
+            (Some(value), Some(count)) => format!("{} ({} times)", value, count),
+            _ => duplicate.to_string(),
+        })
+        .collect();
+    Err(format!(
+        "{}.{} must be unique but these values are shared: {}. Rename or delete the extra documents, then run `migrate` again",
+        collection, field, values.join(", ")
+    ).into())
+}
+
+/// Unique slugs and usernames, and the published-posts listing
+async fn create_post_and_user_indexes(db: &Database) -> MigrationResult {
+    ensure_unique(db, "posts", "slug").await?;
+    ensure_unique(db, "users", "username").await?;
+    create_index(db, "posts", doc! { "slug": 1 }, true).await?;
+    create_index(db, "posts", doc! { "status": 1, "created_at": -1 }, false).await?;
+    create_index(db, "users", doc! { "username": 1 }, true).await
+}
+
+/// Keep only the most recently seen error group per fingerprint. Two first sightings racing
+/// before the unique index existed could each insert one.
+async fn dedupe_error_groups(db: &Database) -> MigrationResult {
+    let collection = db.collection::<Document>("error_events");
+    let pipeline = vec![
+        doc! { "$sort": { "last_seen": -1 } },
+        doc! { "$group": { "_id": "$fingerprint", "ids": { "$push": "$_id" } } },
+        doc! { "$match": { "ids.1": { "$exists": true } } },
+    ];
+
+    let mut duplicates = collection.aggregate(pipeline).await?;
+    let mut removed = 0;
+    while let Some(group) = duplicates.try_next().await? {
+        let stale: Vec<Bson> = group.get_array("ids")?.iter().skip(1).cloned().collect();
+        removed += collection.delete_many(doc! { "_id": { "$in": stale } }).await?.deleted_count;
+    }
+    if removed > 0 {
+        info!(removed, "Removed duplicate error groups");
+    }
+    Ok(())
+}
+
+/// Time-range and per-IP lookups on the analytics collections, one error group per fingerprint
+async fn create_log_indexes(db: &Database) -> MigrationResult {
+    dedupe_error_groups(db).await?;
+    create_index(db, "request_logs", doc! { "timestamp": 1 }, false).await?;
+    create_index(db, "request_logs", doc! { "ip_address": 1 }, false).await?;
+    create_index(db, "click_logs", doc! { "timestamp": 1 }, false).await?;
+    create_index(db, "performance_logs", doc! { "timestamp": 1 }, false).await?;
+    create_index(db, "daily_stats", doc! { "date": 1, "excludes_bots": 1 }, true).await?;
+    create_index(db, "error_events", doc! { "fingerprint": 1 }, true).await
+}
+
+/// Migrations recorded in `_migrations`, oldest first
+pub async fn applied_migrations(db: &Database) -> Result<Vec<AppliedMigration>, mongodb::error::Error> {
+    let collection = db.collection::<AppliedMigration>(MIGRATIONS_COLLECTION);
+    let cursor = collection.find(doc! {}).sort(doc! { "_id": 1 }).await?;
+    cursor.try_collect().await
+}
+
+/// Migrations not applied yet, in the order they would run
+pub async fn pending_migrations(db: &Database) -> Result<Vec<&'static Migration>, mongodb::error::Error> {
+    let applied = applied_migrations(db).await?;
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|migration| !applied.iter().any(|done| done.version == migration.version))
+        .collect())
+}
+
+/// Apply pending migrations in order, recording each as it completes. Stops at the first failure,
+/// so a fixed migration is retried on the next run. Returns the versions applied.
+#[instrument(skip_all)]
+pub async fn run_pending(db: &Database) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+    let collection = db.collection::<AppliedMigration>(MIGRATIONS_COLLECTION);
+    let mut applied = Vec::new();
+
+    for migration in pending_migrations(db).await? {
+        info!(version = migration.version, name = migration.name, "Applying migration");
+        let start = Utc::now();
+        (migration.run)(db).await?;
+
+        // Upserted so two instances starting at once don't fail on the same version
+        let record = AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+            applied_at: BsonDateTime::now(),
+            duration_ms: (Utc::now() - start).num_milliseconds(),
+        };
+        collection
+            .replace_one(doc! { "_id": migration.version }, &record)
+            .upsert(true)
+            .await?;
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_are_increasing() {
+        assert!(MIGRATIONS.windows(2).all(|pair| pair[0].version < pair[1].version));
+        assert!(MIGRATIONS.iter().all(|migration| migration.version > 0));
+    }
+}
@@ -1,6 +1,9 @@
 pub mod connection;
 pub mod posts;
 pub mod admin;
+pub mod repository;
+pub mod migrations;
 
-pub use connection::{MongoDb, connect_with_retry, verify_connection};
+pub use connection::{MongoDb, check_server_version, connect_with_retry, verify_connection};
+pub use repository::{PostRepository, UserRepository};
 
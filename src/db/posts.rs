@@ -1,8 +1,10 @@
 use mongodb::{Database, bson::{doc, oid::ObjectId, DateTime as BsonDateTime}};
 use crate::types::{Post, PostStatus, CreatePost, UpdatePost};
 use futures::stream::TryStreamExt;
+use tracing::instrument;
 
 /// Create a new post
+#[instrument(level = "debug", skip_all)]
 pub async fn create_post(
     db: &Database,
     author_id: ObjectId,
@@ -44,6 +46,7 @@ pub async fn create_post(
 }
 
 /// Get post by ID
+#[instrument(level = "debug", skip_all)]
 pub async fn get_post_by_id(
     db: &Database,
     id: ObjectId,
@@ -53,6 +56,7 @@ pub async fn get_post_by_id(
 }
 
 /// Get post by slug
+#[instrument(level = "debug", skip_all)]
 pub async fn get_post_by_slug(
     db: &Database,
     slug: &str,
@@ -79,6 +83,7 @@ pub async fn get_post_by_slug(
 }
 
 /// Update a post
+#[instrument(level = "debug", skip_all)]
 pub async fn update_post(
     db: &Database,
     id: ObjectId,
@@ -129,7 +134,28 @@ pub async fn update_post(
     get_post_by_id(db, id).await
 }
 
+/// Insert a post as-is, or replace the one with the same ID (used by imports)
+#[instrument(level = "debug", skip_all)]
+pub async fn save_post(
+    db: &Database,
+    post: &Post,
+) -> Result<(), mongodb::error::Error> {
+    let collection = db.collection::<Post>("posts");
+    
+    match post.id {
+        Some(id) => {
+            collection.replace_one(doc! { "_id": id }, post).upsert(true).await?;
+        }
+        None => {
+            collection.insert_one(post).await?;
+        }
+    }
+    
+    Ok(())
+}
+
 /// Delete a post
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_post(
     db: &Database,
     id: ObjectId,
@@ -141,6 +167,7 @@ pub async fn delete_post(
 }
 
 /// List posts with filtering and pagination
+#[instrument(level = "debug", skip_all)]
 pub async fn list_posts(
     db: &Database,
     status: Option<PostStatus>,
@@ -0,0 +1,146 @@
+// src/db/repository/memory.rs
+use std::cmp::Reverse;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use async_trait::async_trait;
+use bcrypt::{hash, DEFAULT_COST};
+use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use crate::types::{Post, PostStatus, User};
+use super::{PostRepository, UserRepository};
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// `PostRepository` holding posts in process memory; for tests and throwaway instances
+#[derive(Debug, Default)]
+pub struct MemoryPostRepository {
+    posts: RwLock<Vec<Post>>,
+}
+
+impl MemoryPostRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait(?Send)]
+impl PostRepository for MemoryPostRepository {
+    async fn save_post(&self, post: &Post) -> Result<(), Box<dyn std::error::Error>> {
+        let mut post = post.clone();
+        let id = *post.id.get_or_insert_with(ObjectId::new);
+        let mut posts = write(&self.posts);
+        posts.retain(|existing| existing.id != Some(id));
+        posts.push(post);
+        Ok(())
+    }
+
+    async fn list_posts(&self, status: Option<PostStatus>, limit: i64, skip: u64) -> Result<Vec<Post>, Box<dyn std::error::Error>> {
+        let mut posts: Vec<Post> = read(&self.posts)
+            .iter()
+            .filter(|post| status.as_ref().is_none_or(|status| post.status == *status))
+            .cloned()
+            .collect();
+        posts.sort_by_key(|post| Reverse(post.created_at));
+
+        // MongoDB treats a limit of 0 as no limit and a negative one as its absolute value
+        let limit = match limit.unsigned_abs() {
+            0 => usize::MAX,
+            limit => limit as usize,
+        };
+        Ok(posts.into_iter().skip(skip as usize).take(limit).collect())
+    }
+}
+
+/// `UserRepository` holding users in process memory; for tests and throwaway instances
+#[derive(Debug, Default)]
+pub struct MemoryUserRepository {
+    users: RwLock<Vec<User>>,
+}
+
+impl MemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait(?Send)]
+impl UserRepository for MemoryUserRepository {
+    async fn get_admin_user(&self) -> Result<Option<User>, Box<dyn std::error::Error>> {
+        Ok(read(&self.users).first().cloned())
+    }
+
+    async fn set_password(&self, username: &str, password: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let password_hash = hash(password, DEFAULT_COST)?;
+        match write(&self.users).iter_mut().find(|user| user.username == username) {
+            Some(user) => {
+                user.password_hash = password_hash;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn initialize_admin(&self, username: &str, password: &str, email: &str) -> Result<User, Box<dyn std::error::Error>> {
+        let user = User {
+            id: Some(ObjectId::new()),
+            username: username.to_string(),
+            password_hash: hash(password, DEFAULT_COST)?,
+            email: email.to_string(),
+            created_at: BsonDateTime::now(),
+            last_login: None,
+        };
+
+        write(&self.users).push(user.clone());
+        Ok(user)
+    }
+
+    async fn admin_exists(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(!read(&self.users).is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(title: &str, status: PostStatus, created_ms: i64) -> Post {
+        Post {
+            id: Some(ObjectId::new()),
+            title: title.to_string(),
+            slug: title.to_lowercase().replace(' ', "-"),
+            content: "Content".to_string(),
+            excerpt: None,
+            status,
+            author_id: ObjectId::new(),
+            created_at: BsonDateTime::from_millis(created_ms),
+            updated_at: BsonDateTime::from_millis(created_ms),
+            published_at: None,
+            tags: Vec::new(),
+            views: 0,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_memory_post_repository() {
+        let repo = MemoryPostRepository::new();
+        let mut draft = post("First Draft", PostStatus::Draft, 1_000);
+        repo.save_post(&draft).await.unwrap();
+        repo.save_post(&post("Hello World", PostStatus::Published, 2_000)).await.unwrap();
+
+        let published = repo.list_posts(Some(PostStatus::Published), 5, 0).await.unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].slug, "hello-world");
+
+        // Saving a post with an existing ID replaces it
+        draft.status = PostStatus::Published;
+        repo.save_post(&draft).await.unwrap();
+        let all = repo.list_posts(None, 0, 0).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].slug, "first-draft");
+        assert_eq!(repo.list_posts(Some(PostStatus::Published), -1, 0).await.unwrap()[0].slug, "hello-world");
+    }
+}
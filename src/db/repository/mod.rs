@@ -0,0 +1,38 @@
+// src/db/repository/mod.rs
+pub mod mongo;
+/// In-memory repositories backing the route and CLI tests
+#[cfg(test)]
+pub mod memory;
+
+use async_trait::async_trait;
+use crate::types::{Post, PostStatus, User};
+
+#[cfg(test)]
+pub use memory::{MemoryPostRepository, MemoryUserRepository};
+pub use mongo::{MongoPostRepository, MongoUserRepository};
+
+/// Blog post storage, injected into handlers as `web::Data<dyn PostRepository>`
+#[async_trait(?Send)]
+pub trait PostRepository: Send + Sync {
+    /// Insert a post with its ID, timestamps and views as given, replacing any post with that ID
+    async fn save_post(&self, post: &Post) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Posts newest first, optionally only those with `status`
+    async fn list_posts(&self, status: Option<PostStatus>, limit: i64, skip: u64) -> Result<Vec<Post>, Box<dyn std::error::Error>>;
+}
+
+/// Admin account storage, injected into handlers as `web::Data<dyn UserRepository>` and used by
+/// the admin commands of the CLI
+#[async_trait(?Send)]
+pub trait UserRepository: Send + Sync {
+    /// The admin user (the first user in the system)
+    async fn get_admin_user(&self) -> Result<Option<User>, Box<dyn std::error::Error>>;
+
+    /// Returns whether the user exists
+    async fn set_password(&self, username: &str, password: &str) -> Result<bool, Box<dyn std::error::Error>>;
+
+    /// Create the admin user (run this once during setup)
+    async fn initialize_admin(&self, username: &str, password: &str, email: &str) -> Result<User, Box<dyn std::error::Error>>;
+
+    async fn admin_exists(&self) -> Result<bool, Box<dyn std::error::Error>>;
+}
@@ -0,0 +1,58 @@
+// src/db/repository/mongo.rs
+use async_trait::async_trait;
+use mongodb::Database;
+use crate::db::{admin, posts};
+use crate::types::{Post, PostStatus, User};
+use super::{PostRepository, UserRepository};
+
+/// Posts in the `posts` collection, through the functions in `db::posts`
+pub struct MongoPostRepository {
+    database: Database,
+}
+
+impl MongoPostRepository {
+    pub fn new(database: &Database) -> Self {
+        MongoPostRepository { database: database.clone() }
+    }
+}
+
+#[async_trait(?Send)]
+impl PostRepository for MongoPostRepository {
+    async fn save_post(&self, post: &Post) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(posts::save_post(&self.database, post).await?)
+    }
+
+    async fn list_posts(&self, status: Option<PostStatus>, limit: i64, skip: u64) -> Result<Vec<Post>, Box<dyn std::error::Error>> {
+        Ok(posts::list_posts(&self.database, status, limit, skip).await?)
+    }
+}
+
+/// Users in the `users` collection, through the functions in `db::admin`
+pub struct MongoUserRepository {
+    database: Database,
+}
+
+impl MongoUserRepository {
+    pub fn new(database: &Database) -> Self {
+        MongoUserRepository { database: database.clone() }
+    }
+}
+
+#[async_trait(?Send)]
+impl UserRepository for MongoUserRepository {
+    async fn get_admin_user(&self) -> Result<Option<User>, Box<dyn std::error::Error>> {
+        Ok(admin::get_admin_user(&self.database).await?)
+    }
+
+    async fn set_password(&self, username: &str, password: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        admin::set_password(&self.database, username, password).await
+    }
+
+    async fn initialize_admin(&self, username: &str, password: &str, email: &str) -> Result<User, Box<dyn std::error::Error>> {
+        admin::initialize_admin(&self.database, username, password, email).await
+    }
+
+    async fn admin_exists(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(admin::admin_exists(&self.database).await?)
+    }
+}
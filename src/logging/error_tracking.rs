@@ -0,0 +1,99 @@
+// src/logging/error_tracking.rs
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use sha2::{Digest, Sha256};
+
+/// One failed request, as captured by the `RequestLogging` middleware
+#[derive(Debug, Clone)]
+pub struct ErrorEvent {
+    pub timestamp: mongodb::bson::DateTime,
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    pub route: String,
+    pub status_code: u16,
+    pub message: String,
+    /// Only captured in dev: the backtrace of where an `AppError` was raised, or the debug output
+    /// of any other error
+    pub details: Option<String>,
+}
+
+/// Error events sharing a fingerprint, stored in `error_events`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorGroup {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub fingerprint: String,
+    pub status_code: u16,
+    pub method: String,
+    pub route: String,
+    pub message: String,
+    pub first_seen: mongodb::bson::DateTime,
+    pub last_seen: mongodb::bson::DateTime,
+    pub count: u64,
+    pub last_path: String,
+    pub last_request_id: String,
+    /// Newest last, capped at `RECENT_REQUEST_IDS`
+    #[serde(default)]
+    pub recent_request_ids: Vec<String>,
+    #[serde(default)]
+    pub details: Option<String>,
+    #[serde(default)]
+    pub resolved: bool,
+    #[serde(default)]
+    pub resolved_at: Option<mongodb::bson::DateTime>,
+}
+
+pub const RECENT_REQUEST_IDS: i32 = 10;
+
+impl ErrorEvent {
+    /// Groups errors by status, method, route and message, with digit runs masked so ids and counts
+    /// in messages don't split a group
+    pub fn fingerprint(&self) -> String {
+        let mut message = String::with_capacity(self.message.len());
+        for c in self.message.chars() {
+            if !c.is_ascii_digit() {
+                message.push(c);
+            } else if !message.ends_with('#') {
+                message.push('#');
+            }
+        }
+
+        let digest = Sha256::new()
+            .chain_update(self.status_code.to_string())
+            .chain_update([0])
+            .chain_update(&self.method)
+            .chain_update([0])
+            .chain_update(&self.route)
+            .chain_update([0])
+            .chain_update(message)
+            .finalize();
+        digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(path: &str, message: &str) -> ErrorEvent {
+        ErrorEvent {
+            timestamp: mongodb::bson::DateTime::now(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            method: "GET".to_string(),
+            path: path.to_string(),
+            route: "/blog/{id}".to_string(),
+            status_code: 500,
+            message: message.to_string(),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let first = event("/blog/1", "Post 1 failed to render");
+        let second = event("/blog/42", "Post 42 failed to render");
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), event("/blog/1", "Database unavailable").fingerprint());
+    }
+}
@@ -0,0 +1,202 @@
+// src/logging/export.rs
+use std::ops::Bound;
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use super::filter::{Filter, NumberField, TextField, TextMatch};
+use super::{ClickLog, request_logger::RequestLog};
+
+/// Query parameters shared by the export endpoints; every field is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>, // inclusive, UTC
+    pub to: Option<NaiveDate>,   // inclusive, UTC
+    pub path: Option<String>,    // path prefix
+    pub method: Option<String>,
+    pub status: Option<u16>,
+    pub country: Option<String>,
+    pub event_type: Option<String>, // clicks only
+    #[serde(default)]
+    pub exclude_bots: bool,
+}
+
+impl ExportFilter {
+    /// The days from `from` through `to`, when either is set
+    fn date_filter(&self) -> Option<Filter> {
+        let start = self.from.map_or(Bound::Unbounded, |from| Bound::Included(start_of_day(from)));
+        let end = self.to.and_then(|to| to.succ_opt()).map_or(Bound::Unbounded, |to| Bound::Excluded(start_of_day(to)));
+        (self.from.is_some() || self.to.is_some()).then_some(Filter::Time((start, end)))
+    }
+
+    pub fn request_filter(&self) -> Filter {
+        let mut filters: Vec<Filter> = self.date_filter().into_iter().collect();
+        if let Some(path) = &self.path {
+            filters.push(Filter::Text(TextField::Path, TextMatch::prefix(path)));
+        }
+        if let Some(method) = &self.method {
+            filters.push(Filter::Text(TextField::Method, TextMatch::Exact(method.to_uppercase())));
+        }
+        if let Some(status) = self.status {
+            let status = status as i64;
+            filters.push(Filter::Number(NumberField::StatusCode, (Bound::Included(status), Bound::Included(status))));
+        }
+        if let Some(country) = &self.country {
+            filters.push(Filter::Text(TextField::Country, TextMatch::Exact(country.clone())));
+        }
+        if self.exclude_bots {
+            filters.push(Filter::Bot(false));
+        }
+        Filter::And(filters)
+    }
+
+    pub fn click_filter(&self) -> Filter {
+        let mut filters: Vec<Filter> = self.date_filter().into_iter().collect();
+        if let Some(path) = &self.path {
+            filters.push(Filter::Text(TextField::PagePath, TextMatch::prefix(path)));
+        }
+        if let Some(event_type) = &self.event_type {
+            filters.push(Filter::Text(TextField::EventType, TextMatch::Exact(event_type.clone())));
+        }
+        Filter::And(filters)
+    }
+}
+
+fn start_of_day(day: NaiveDate) -> mongodb::bson::DateTime {
+    let start = Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default());
+    mongodb::bson::DateTime::from_millis(start.timestamp_millis())
+}
+
+pub(crate) fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A log type that can be written as one CSV row or one NDJSON line
+pub trait ExportRecord: Serialize {
+    const CSV_HEADER: &'static [&'static str];
+
+    fn csv_row(&self) -> Vec<String>;
+    fn timestamp(&self) -> mongodb::bson::DateTime;
+}
+
+impl ExportRecord for RequestLog {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "timestamp", "request_id", "ip_address", "visitor_id", "method", "path", "route",
+        "status_code", "response_time_ms", "country", "city", "browser", "os", "device_type",
+        "is_bot", "referrer_host", "utm_source", "utm_medium", "utm_campaign", "user_agent",
+    ];
+
+    fn csv_row(&self) -> Vec<String> {
+        let ua = self.user_agent_info.as_ref();
+        vec![
+            rfc3339(self.timestamp),
+            self.request_id.clone(),
+            self.ip_address.clone(),
+            self.visitor_id.clone().unwrap_or_default(),
+            self.method.clone(),
+            self.path.clone(),
+            self.route.clone().unwrap_or_default(),
+            self.status_code.to_string(),
+            self.response_time_ms.to_string(),
+            self.country.clone().unwrap_or_default(),
+            self.city.clone().unwrap_or_default(),
+            ua.map(|ua| ua.browser.clone()).unwrap_or_default(),
+            ua.map(|ua| ua.os.clone()).unwrap_or_default(),
+            ua.map(|ua| ua.device_type.clone()).unwrap_or_default(),
+            ua.map(|ua| ua.is_bot.to_string()).unwrap_or_default(),
+            self.referrer_host.clone().unwrap_or_default(),
+            self.utm_source.clone().unwrap_or_default(),
+            self.utm_medium.clone().unwrap_or_default(),
+            self.utm_campaign.clone().unwrap_or_default(),
+            self.user_agent.clone(),
+        ]
+    }
+
+    fn timestamp(&self) -> mongodb::bson::DateTime {
+        self.timestamp
+    }
+}
+
+impl ExportRecord for ClickLog {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "timestamp", "request_id", "ip_address", "event_type", "element", "page_path", "user_agent",
+    ];
+
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            rfc3339(self.timestamp),
+            self.request_id.clone(),
+            self.ip_address.clone(),
+            self.event_type.clone(),
+            self.element.clone(),
+            self.page_path.clone(),
+            self.user_agent.clone(),
+        ]
+    }
+
+    fn timestamp(&self) -> mongodb::bson::DateTime {
+        self.timestamp
+    }
+}
+
+fn rfc3339(timestamp: mongodb::bson::DateTime) -> String {
+    timestamp.try_to_rfc3339_string().unwrap_or_default()
+}
+
+/// One CSV line, quoted as needed
+pub fn csv_line<S: AsRef<str>>(fields: &[S]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields.iter().map(|field| field.as_ref()))?;
+    Ok(writer.into_inner().map_err(|e| e.into_error())?)
+}
+
+/// One NDJSON line; the `_id` is dropped and the timestamp written as RFC 3339 instead of extended JSON
+pub fn ndjson_line<T: ExportRecord>(record: &T) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut value = serde_json::to_value(record)?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove("_id");
+        object.insert("timestamp".to_string(), rfc3339(record.timestamp()).into());
+    }
+
+    let mut line = serde_json::to_vec(&value)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_line_quotes_fields() {
+        let line = csv_line(&["/a,b", "say \"hi\"", "plain"]).unwrap();
+        assert_eq!(String::from_utf8(line).unwrap(), "\"/a,b\",\"say \"\"hi\"\"\",plain\n");
+    }
+
+    #[test]
+    fn test_request_filter() {
+        let filter = ExportFilter {
+            from: NaiveDate::from_ymd_opt(2024, 1, 15),
+            to: NaiveDate::from_ymd_opt(2024, 1, 15),
+            path: Some("/blog.old".to_string()),
+            status: Some(404),
+            ..Default::default()
+        };
+        let Filter::And(filters) = filter.request_filter() else {
+            panic!("expected the conditions to be ANDed");
+        };
+        assert_eq!(filters.len(), 3);
+
+        let Filter::Time((Bound::Included(start), Bound::Excluded(end))) = filters[0] else {
+            panic!("expected a day range, got {:?}", filters[0]);
+        };
+        assert_eq!(end.timestamp_millis() - start.timestamp_millis(), 24 * 60 * 60 * 1000);
+        assert!(matches!(&filters[1], Filter::Text(TextField::Path, TextMatch::Pattern { regex, .. }) if regex.as_str() == "^/blog\\.old"));
+        assert!(matches!(filters[2], Filter::Number(NumberField::StatusCode, (Bound::Included(404), Bound::Included(404)))));
+    }
+}
@@ -0,0 +1,367 @@
+// src/logging/filter.rs
+use std::net::IpAddr;
+use std::ops::{Bound, RangeBounds};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ipnet::IpNet;
+use regex::{Regex, RegexBuilder};
+use super::export::escape_regex;
+use super::{ClickLog, request_logger::RequestLog};
+
+/// A parsed log filter. Each store evaluates it its own way: MongoDB translates it into a query
+/// document, the in-memory store calls `matches`.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Every filter matches; no filters match everything
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    /// The field is set and its value matches
+    Text(TextField, TextMatch),
+    Number(NumberField, (Bound<i64>, Bound<i64>)),
+    Time((Bound<mongodb::bson::DateTime>, Bound<mongodb::bson::DateTime>)),
+    /// Whether the user agent was classified as a bot; unparsed user agents aren't bots
+    Bot(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    Path,
+    Route,
+    IpAddress,
+    Method,
+    Country,
+    Browser,
+    ReferrerHost,
+    VisitorId,
+    PagePath,
+    EventType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberField {
+    StatusCode,
+    ResponseTimeMs,
+}
+
+#[derive(Debug, Clone)]
+pub enum TextMatch {
+    Exact(String),
+    /// A regex in the syntax MongoDB and the `regex` crate share
+    Pattern { regex: Regex, ignore_case: bool },
+}
+
+impl TextMatch {
+    fn pattern(pattern: &str, ignore_case: bool) -> Result<Self, String> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| format!("invalid pattern '{}': {}", pattern, e))?;
+        Ok(TextMatch::Pattern { regex, ignore_case })
+    }
+
+    /// Values starting with `prefix`
+    pub fn prefix(prefix: &str) -> Self {
+        Self::pattern(&format!("^{}", escape_regex(prefix)), false).expect("escaped patterns are valid regexes")
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            TextMatch::Exact(expected) => value == expected,
+            TextMatch::Pattern { regex, .. } => regex.is_match(value),
+        }
+    }
+}
+
+/// A log record a `Filter` can be evaluated against; fields a record doesn't have are `None`
+pub trait Filterable {
+    fn text_field(&self, field: TextField) -> Option<&str>;
+    fn number_field(&self, field: NumberField) -> Option<i64>;
+    fn logged_at(&self) -> mongodb::bson::DateTime;
+    fn is_bot(&self) -> bool;
+}
+
+impl Filterable for RequestLog {
+    fn text_field(&self, field: TextField) -> Option<&str> {
+        match field {
+            TextField::Path => Some(&self.path),
+            TextField::Route => self.route.as_deref(),
+            TextField::IpAddress => Some(&self.ip_address),
+            TextField::Method => Some(&self.method),
+            TextField::Country => self.country.as_deref(),
+            TextField::Browser => self.user_agent_info.as_ref().map(|info| info.browser.as_str()),
+            TextField::ReferrerHost => self.referrer_host.as_deref(),
+            TextField::VisitorId => self.visitor_id.as_deref(),
+            TextField::PagePath | TextField::EventType => None,
+        }
+    }
+
+    fn number_field(&self, field: NumberField) -> Option<i64> {
+        match field {
+            NumberField::StatusCode => Some(self.status_code as i64),
+            NumberField::ResponseTimeMs => Some(self.response_time_ms as i64),
+        }
+    }
+
+    fn logged_at(&self) -> mongodb::bson::DateTime {
+        self.timestamp
+    }
+
+    fn is_bot(&self) -> bool {
+        self.user_agent_info.as_ref().is_some_and(|info| info.is_bot)
+    }
+}
+
+impl Filterable for ClickLog {
+    fn text_field(&self, field: TextField) -> Option<&str> {
+        match field {
+            TextField::IpAddress => Some(&self.ip_address),
+            TextField::PagePath => Some(&self.page_path),
+            TextField::EventType => Some(&self.event_type),
+            _ => None,
+        }
+    }
+
+    fn number_field(&self, _: NumberField) -> Option<i64> {
+        None
+    }
+
+    fn logged_at(&self) -> mongodb::bson::DateTime {
+        self.timestamp
+    }
+
+    fn is_bot(&self) -> bool {
+        false
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, record: &impl Filterable) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(record)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(record)),
+            Filter::Not(filter) => !filter.matches(record),
+            Filter::Text(field, text) => record.text_field(*field).is_some_and(|value| text.matches(value)),
+            Filter::Number(field, range) => record.number_field(*field).is_some_and(|value| range.contains(&value)),
+            Filter::Time(range) => range.contains(&record.logged_at()),
+            Filter::Bot(is_bot) => record.is_bot() == *is_bot,
+        }
+    }
+}
+
+/// Parse a request filter such as `status:5xx path:/blog/* ip:1.2.3.0/24 ms:>500 after:2026-01-01`.
+/// Terms are ANDed, a leading `-` negates a term, and bare words search path, IP and method.
+pub fn parse_request_filter(query: &str) -> Result<Filter, String> {
+    let mut filters = Vec::new();
+
+    for term in query.split_whitespace() {
+        let (negated, term) = match term.strip_prefix('-') {
+            Some(rest) if rest.contains(':') => (true, rest),
+            _ => (false, term),
+        };
+
+        let filter = match term.split_once(':') {
+            Some((key, value)) if !value.is_empty() => parse_term(&key.to_lowercase(), value)?,
+            Some((key, _)) => return Err(format!("missing value for '{}'", key)),
+            None => free_text(term)?,
+        };
+
+        filters.push(if negated { Filter::Not(Box::new(filter)) } else { filter });
+    }
+
+    Ok(match filters.len() {
+        1 => filters.remove(0),
+        _ => Filter::And(filters),
+    })
+}
+
+fn parse_term(key: &str, value: &str) -> Result<Filter, String> {
+    match key {
+        "status" => parse_status(value),
+        "path" => Ok(Filter::Text(TextField::Path, glob(value)?)),
+        "route" => Ok(Filter::Text(TextField::Route, glob(value)?)),
+        "ip" => parse_ip(value),
+        "ms" => Ok(Filter::Number(NumberField::ResponseTimeMs, parse_number(value)?)),
+        "method" => Ok(Filter::Text(TextField::Method, TextMatch::Exact(value.to_uppercase()))),
+        "country" => Ok(Filter::Text(TextField::Country, glob(value)?)),
+        "browser" => Ok(Filter::Text(TextField::Browser, glob(value)?)),
+        "referrer" => Ok(Filter::Text(TextField::ReferrerHost, glob(value)?)),
+        "visitor" => Ok(Filter::Text(TextField::VisitorId, TextMatch::Exact(value.to_string()))),
+        "bot" => match value {
+            "true" | "yes" | "1" => Ok(Filter::Bot(true)),
+            "false" | "no" | "0" => Ok(Filter::Bot(false)),
+            _ => Err(format!("bot expects true or false, got '{}'", value)),
+        },
+        "after" => Ok(Filter::Time((Bound::Included(parse_time(value)?), Bound::Unbounded))),
+        "before" => Ok(Filter::Time((Bound::Unbounded, Bound::Excluded(parse_time(value)?)))),
+        _ => Err(format!(
+            "unknown filter '{}' (expected status, path, route, ip, ms, method, country, browser, referrer, visitor, bot, after or before)",
+            key
+        )),
+    }
+}
+
+fn free_text(word: &str) -> Result<Filter, String> {
+    let pattern = TextMatch::pattern(&escape_regex(word), true)?;
+    Ok(Filter::Or(vec![
+        Filter::Text(TextField::Path, pattern.clone()),
+        Filter::Text(TextField::IpAddress, pattern.clone()),
+        Filter::Text(TextField::Method, pattern),
+    ]))
+}
+
+/// Exact value, or an anchored pattern when the value contains `*`
+fn glob(value: &str) -> Result<TextMatch, String> {
+    if !value.contains('*') {
+        return Ok(TextMatch::Exact(value.to_string()));
+    }
+
+    let pattern: Vec<String> = value.split('*').map(escape_regex).collect();
+    TextMatch::pattern(&format!("^{}$", pattern.join(".*")), false)
+}
+
+/// `404`, `5xx`, or a comparison like `>=400`
+fn parse_status(value: &str) -> Result<Filter, String> {
+    let lower = value.to_lowercase();
+    if let Some(class) = lower.strip_suffix("xx") {
+        let class: i64 = class.parse().ok().filter(|c| (1..=5).contains(c))
+            .ok_or_else(|| format!("invalid status class '{}'", value))?;
+        return Ok(Filter::Number(NumberField::StatusCode, (Bound::Included(class * 100), Bound::Excluded((class + 1) * 100))));
+    }
+    Ok(Filter::Number(NumberField::StatusCode, parse_number(value)?))
+}
+
+/// `500`, `>500`, `>=500`, `<500`, `<=500` or `100..500` (inclusive)
+fn parse_number(value: &str) -> Result<(Bound<i64>, Bound<i64>), String> {
+    let number = |s: &str| s.parse::<i64>().map_err(|_| format!("invalid number '{}'", value));
+
+    if let Some((low, high)) = value.split_once("..") {
+        return Ok((Bound::Included(number(low)?), Bound::Included(number(high)?)));
+    }
+
+    Ok(if let Some(rest) = value.strip_prefix(">=") {
+        (Bound::Included(number(rest)?), Bound::Unbounded)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (Bound::Unbounded, Bound::Included(number(rest)?))
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (Bound::Excluded(number(rest)?), Bound::Unbounded)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (Bound::Unbounded, Bound::Excluded(number(rest)?))
+    } else {
+        let exact = number(value)?;
+        (Bound::Included(exact), Bound::Included(exact))
+    })
+}
+
+/// Exact IP, glob (`10.0.*`) or IPv4 CIDR. IPs are stored as text, so a CIDR becomes a prefix regex.
+fn parse_ip(value: &str) -> Result<Filter, String> {
+    if value.contains('*') || value.parse::<IpAddr>().is_ok() {
+        return Ok(Filter::Text(TextField::IpAddress, glob(value)?));
+    }
+
+    match value.parse::<IpNet>() {
+        Ok(IpNet::V4(net)) => {
+            let pattern = ipv4_cidr_regex(net.network().octets(), net.prefix_len());
+            Ok(Filter::Text(TextField::IpAddress, TextMatch::pattern(&pattern, false)?))
+        }
+        Ok(IpNet::V6(_)) => Err("IPv6 ranges aren't supported, use a glob like ip:2001:db8:*".to_string()),
+        Err(_) => Err(format!("invalid IP or CIDR '{}'", value)),
+    }
+}
+
+fn ipv4_cidr_regex(octets: [u8; 4], prefix_len: u8) -> String {
+    let full = (prefix_len / 8) as usize;
+    let mut pattern: String = octets[..full].iter().map(|o| format!("{}\\.", o)).collect();
+
+    let remaining_bits = prefix_len % 8;
+    if remaining_bits > 0 {
+        let start = octets[full] as u16;
+        let count = 1u16 << (8 - remaining_bits);
+        let values: Vec<String> = (start..start + count).map(|o| o.to_string()).collect();
+        pattern.push_str(&format!("({})", values.join("|")));
+        pattern.push_str(if full < 3 { "\\." } else { "$" });
+    } else if full == 4 {
+        pattern.pop();
+        pattern.pop();
+        pattern.push('$');
+    }
+
+    format!("^{}", pattern)
+}
+
+/// `2026-01-01` (midnight UTC) or a full RFC 3339 timestamp
+fn parse_time(value: &str) -> Result<mongodb::bson::DateTime, String> {
+    let time = if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+    } else {
+        DateTime::parse_from_rfc3339(value)
+            .map_err(|_| format!("invalid date '{}' (use YYYY-MM-DD)", value))?
+            .with_timezone(&Utc)
+    };
+    Ok(mongodb::bson::DateTime::from_millis(time.timestamp_millis()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_filter() {
+        let Filter::And(filters) = parse_request_filter("status:5xx path:/blog/* ms:>500 -method:head").unwrap() else {
+            panic!("expected the terms to be ANDed");
+        };
+        assert_eq!(filters.len(), 4);
+
+        assert!(matches!(filters[0], Filter::Number(NumberField::StatusCode, (Bound::Included(500), Bound::Excluded(600)))));
+        assert!(matches!(&filters[1], Filter::Text(TextField::Path, TextMatch::Pattern { regex, .. }) if regex.as_str() == "^/blog/.*$"));
+        assert!(matches!(filters[2], Filter::Number(NumberField::ResponseTimeMs, (Bound::Excluded(500), Bound::Unbounded))));
+        assert!(matches!(&filters[3], Filter::Not(filter) if matches!(&**filter, Filter::Text(TextField::Method, TextMatch::Exact(method)) if method == "HEAD")));
+
+        assert!(matches!(parse_request_filter("").unwrap(), Filter::And(filters) if filters.is_empty()));
+        assert!(parse_request_filter("colour:red").is_err());
+        assert!(parse_request_filter("status:7xx").is_err());
+        assert!(parse_request_filter("after:yesterday").is_err());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let record = RequestLog {
+            id: None,
+            timestamp: mongodb::bson::DateTime::from_millis(1_767_225_600_000), // 2026-01-01
+            request_id: "req-1".to_string(),
+            ip_address: "10.0.17.4".to_string(),
+            visitor_id: None,
+            user_agent: "curl/8.0".to_string(),
+            user_agent_info: None,
+            method: "GET".to_string(),
+            path: "/blog/hello".to_string(),
+            route: None,
+            status_code: 503,
+            response_time_ms: 800,
+            location: None,
+            country: None,
+            city: None,
+            referrer: None,
+            referrer_host: None,
+            utm_source: None,
+            utm_medium: None,
+            utm_campaign: None,
+        };
+        let matches = |query: &str| parse_request_filter(query).unwrap().matches(&record);
+
+        assert!(matches("status:5xx path:/blog/* ms:>500 -method:head"));
+        assert!(matches("ip:10.0.16.0/22 bot:false HELLO after:2026-01-01"));
+        assert!(matches(""));
+        assert!(!matches("status:404"));
+        assert!(!matches("-path:/blog/*"));
+        assert!(!matches("country:NL"));
+        assert!(matches("-country:NL"));
+        assert!(!matches("before:2026-01-01"));
+    }
+
+    #[test]
+    fn test_ipv4_cidr_regex() {
+        assert_eq!(ipv4_cidr_regex([1, 2, 3, 0], 24), "^1\\.2\\.3\\.");
+        assert_eq!(ipv4_cidr_regex([10, 0, 16, 0], 22), "^10\\.0\\.(16|17|18|19)\\.");
+        assert_eq!(ipv4_cidr_regex([1, 2, 3, 4], 32), "^1\\.2\\.3\\.4$");
+    }
+}
@@ -0,0 +1,78 @@
+// src/logging/geoip.rs
+use std::net::IpAddr;
+use lazy_static::lazy_static;
+use maxminddb::{geoip2, Reader};
+use crate::config::GEOIP_DATABASE;
+use tracing::{info, warn};
+
+lazy_static! {
+    /// Loaded once from `GEOIP_DATABASE`; `None` disables enrichment entirely
+    pub static ref GEOIP: Option<GeoIp> = GEOIP_DATABASE.as_deref().and_then(|path| {
+        match GeoIp::open(path) {
+            Ok(geoip) => {
+                info!(path, "GeoIP database loaded");
+                Some(geoip)
+            }
+            Err(e) => {
+                warn!(path, error = %e, "Failed to load GeoIP database");
+                None
+            }
+        }
+    });
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoLocation {
+    pub location: Option<String>, // "latitude,longitude"
+    pub country: Option<String>,
+    pub city: Option<String>,
+}
+
+/// Offline lookups against a MaxMind-format (.mmdb) city database
+pub struct GeoIp {
+    reader: Reader<Vec<u8>>,
+}
+
+impl GeoIp {
+    pub fn open(path: &str) -> Result<Self, maxminddb::MaxMindDBError> {
+        Ok(Self {
+            reader: Reader::open_readfile(path)?,
+        })
+    }
+
+    pub fn lookup(&self, ip: &str) -> Option<GeoLocation> {
+        let ip: IpAddr = ip.parse().ok()?;
+        if !is_public(&ip) {
+            return None;
+        }
+
+        let record: geoip2::City = self.reader.lookup(ip).ok()?;
+        let english_name = |names: Option<std::collections::BTreeMap<&str, &str>>| {
+            names.and_then(|n| n.get("en").map(|name| name.to_string()))
+        };
+
+        let country = record.country.and_then(|c| {
+            let iso_code = c.iso_code.map(str::to_string);
+            english_name(c.names).or(iso_code)
+        });
+        let city = record.city.and_then(|c| english_name(c.names));
+        let location = record.location.and_then(|l| match (l.latitude, l.longitude) {
+            (Some(lat), Some(lon)) => Some(format!("{},{}", lat, lon)),
+            _ => None,
+        });
+
+        Some(GeoLocation { location, country, city })
+    }
+}
+
+/// Look up an IP with the configured database, `None` when GeoIP is disabled or the IP is unknown
+pub fn lookup(ip: &str) -> Option<GeoLocation> {
+    GEOIP.as_ref().and_then(|geoip| geoip.lookup(ip))
+}
+
+fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified()),
+        IpAddr::V6(v6) => !(v6.is_loopback() || v6.is_unspecified() || (v6.segments()[0] & 0xfe00) == 0xfc00),
+    }
+}
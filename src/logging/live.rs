@@ -0,0 +1,110 @@
+// src/logging/live.rs
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// A visitor counts as active if their last request was this recent
+pub const ACTIVE_WINDOW_MS: i64 = 5 * 60 * 1000;
+/// Seconds of request-rate history kept for the sparkline
+pub const RATE_HISTORY_SECS: usize = 60;
+
+/// A logged request, as pushed to `/logs/live` subscribers
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveRequest {
+    pub timestamp: i64, // milliseconds since epoch
+    pub method: String,
+    pub path: String,
+    pub status_code: u16,
+    pub response_time_ms: u64,
+    pub country: Option<String>,
+    pub browser: Option<String>,
+    pub is_bot: bool,
+}
+
+/// Periodic summary sent alongside the request events
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSnapshot {
+    pub active_visitors: usize,
+    pub requests_per_second: Vec<u64>, // oldest first, last entry is the latest complete second
+}
+
+/// Tracks recent traffic in-process; nothing here is persisted
+struct LiveState {
+    last_seen: HashMap<String, i64>,
+    per_second: VecDeque<(i64, u64)>,
+}
+
+lazy_static! {
+    static ref CHANNEL: broadcast::Sender<LiveRequest> = broadcast::channel(256).0;
+    static ref STATE: Mutex<LiveState> = Mutex::new(LiveState {
+        last_seen: HashMap::new(),
+        per_second: VecDeque::new(),
+    });
+}
+
+/// Record a request and push it to any connected live viewers
+pub fn publish(visitor_id: &str, request: LiveRequest) {
+    {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_seen.insert(visitor_id.to_string(), request.timestamp);
+
+        let second = request.timestamp / 1000;
+        match state.per_second.back_mut() {
+            Some((last, count)) if *last == second => *count += 1,
+            _ => state.per_second.push_back((second, 1)),
+        }
+        while state.per_second.len() > RATE_HISTORY_SECS + 1 {
+            state.per_second.pop_front();
+        }
+    }
+
+    // Sending only fails when nobody is listening
+    let _ = CHANNEL.send(request);
+}
+
+pub fn subscribe() -> broadcast::Receiver<LiveRequest> {
+    CHANNEL.subscribe()
+}
+
+pub fn snapshot() -> LiveSnapshot {
+    let now = Utc::now().timestamp_millis();
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.last_seen.retain(|_, seen| now - *seen <= ACTIVE_WINDOW_MS);
+
+    LiveSnapshot {
+        active_visitors: state.last_seen.len(),
+        requests_per_second: rate_history(&state.per_second, now / 1000),
+    }
+}
+
+/// Per-second counts for the `RATE_HISTORY_SECS` complete seconds before `current_second`, zero-filled
+fn rate_history(per_second: &VecDeque<(i64, u64)>, current_second: i64) -> Vec<u64> {
+    let first = current_second - RATE_HISTORY_SECS as i64;
+    (first..current_second)
+        .map(|second| {
+            per_second
+                .iter()
+                .find(|(s, _)| *s == second)
+                .map_or(0, |(_, count)| *count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_history() {
+        let per_second = VecDeque::from(vec![(95, 3), (99, 1), (100, 7)]);
+        let history = rate_history(&per_second, 100);
+
+        assert_eq!(history.len(), RATE_HISTORY_SECS);
+        assert_eq!(history[RATE_HISTORY_SECS - 1], 1);
+        assert_eq!(history[RATE_HISTORY_SECS - 5], 3);
+        assert_eq!(history.iter().sum::<u64>(), 4);
+    }
+}
@@ -0,0 +1,141 @@
+// src/logging/metrics.rs
+use std::sync::atomic::Ordering;
+use lazy_static::lazy_static;
+use mongodb::event::command::CommandEvent;
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use super::performance::{self, ACTIVE_CONNECTIONS, IN_FLIGHT_REQUESTS};
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = Registry::new();
+
+    pub static ref HTTP_REQUESTS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("http_requests_total", "HTTP requests by route, method and status"),
+        &["route", "method", "status"],
+    ));
+
+    pub static ref HTTP_REQUEST_DURATION_SECONDS: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route and method")
+            .buckets(vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+        &["route", "method"],
+    ));
+
+    pub static ref HTTP_REQUESTS_IN_FLIGHT: IntGauge = register(IntGauge::new(
+        "http_requests_in_flight", "Requests currently being handled",
+    ));
+
+    pub static ref HTTP_CONNECTIONS_ACTIVE: IntGauge = register(IntGauge::new(
+        "http_connections_active", "Open HTTP connections",
+    ));
+
+    pub static ref MONGO_COMMAND_DURATION_SECONDS: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new("mongodb_command_duration_seconds", "MongoDB command latency by command name")
+            .buckets(vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+        &["command"],
+    ));
+
+    pub static ref MONGO_COMMAND_ERRORS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("mongodb_command_errors_total", "Failed MongoDB commands by command name"),
+        &["command"],
+    ));
+
+    pub static ref LOG_WRITES_PENDING: IntGauge = register(IntGauge::new(
+        "log_writes_pending", "Log writes queued but not yet persisted",
+    ));
+
+    pub static ref LOG_WRITE_FAILURES_TOTAL: IntCounter = register(IntCounter::new(
+        "log_write_failures_total", "Log writes that failed",
+    ));
+
+    pub static ref LOG_WRITES_DROPPED_TOTAL: IntCounter = register(IntCounter::new(
+        "log_writes_dropped_total", "Log writes dropped because the backlog was full",
+    ));
+
+    pub static ref PROCESS_RESIDENT_MEMORY_BYTES: Gauge = register(Gauge::new(
+        "process_resident_memory_bytes", "Resident memory size in bytes",
+    ));
+
+    pub static ref PROCESS_CPU_SECONDS_TOTAL: Gauge = register(Gauge::new(
+        "process_cpu_seconds_total", "Total user and system CPU time spent in seconds",
+    ));
+
+    pub static ref PROCESS_OPEN_FDS: IntGauge = register(IntGauge::new(
+        "process_open_fds", "Number of open file descriptors",
+    ));
+}
+
+fn register<M>(metric: prometheus::Result<M>) -> M
+where
+    M: prometheus::core::Collector + Clone + 'static,
+{
+    let metric = metric.expect("Invalid metric definition");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("Metric registered twice");
+    metric
+}
+
+/// Record a finished HTTP request
+pub fn observe_request(route: &str, method: &str, status: u16, elapsed_secs: f64) {
+    HTTP_REQUESTS_TOTAL
+        .with_label_values(&[route, method, &status.to_string()])
+        .inc();
+    HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&[route, method])
+        .observe(elapsed_secs);
+}
+
+/// MongoDB command monitoring callback, installed on the client options
+pub fn observe_mongo_command(event: CommandEvent) {
+    match event {
+        CommandEvent::Succeeded(e) => {
+            MONGO_COMMAND_DURATION_SECONDS
+                .with_label_values(&[e.command_name.as_str()])
+                .observe(e.duration.as_secs_f64());
+        }
+        CommandEvent::Failed(e) => {
+            MONGO_COMMAND_DURATION_SECONDS
+                .with_label_values(&[e.command_name.as_str()])
+                .observe(e.duration.as_secs_f64());
+            MONGO_COMMAND_ERRORS_TOTAL
+                .with_label_values(&[e.command_name.as_str()])
+                .inc();
+        }
+        _ => {}
+    }
+}
+
+/// Refresh point-in-time gauges and encode every metric in the Prometheus text format
+pub fn render() -> Result<String, Box<dyn std::error::Error>> {
+    HTTP_REQUESTS_IN_FLIGHT.set(IN_FLIGHT_REQUESTS.load(Ordering::Relaxed) as i64);
+    HTTP_CONNECTIONS_ACTIVE.set(ACTIVE_CONNECTIONS.load(Ordering::Relaxed) as i64);
+
+    if let Some(rss_mb) = performance::read_rss_mb() {
+        PROCESS_RESIDENT_MEMORY_BYTES.set(rss_mb * 1024.0 * 1024.0);
+    }
+    if let Some(ticks) = performance::read_cpu_ticks() {
+        PROCESS_CPU_SECONDS_TOTAL.set(ticks as f64 / *performance::CLOCK_TICKS_PER_SEC);
+    }
+    if let Ok(fds) = std::fs::read_dir("/proc/self/fd") {
+        PROCESS_OPEN_FDS.set(fds.count() as i64);
+    }
+
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_observed_request() {
+        observe_request("/about", "GET", 200, 0.012);
+        let body = render().unwrap();
+        assert!(body.contains(r#"http_requests_total{method="GET",route="/about",status="200"}"#));
+        assert!(body.contains("http_request_duration_seconds_bucket"));
+    }
+}
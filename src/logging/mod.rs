@@ -1,8 +1,20 @@
-
 pub mod request_logger;
 pub mod performance;
-pub mod db_logger;
+pub mod store;
+pub mod rollup;
+pub mod stats;
+pub mod metrics;
+pub mod geoip;
+pub mod user_agent;
+pub mod privacy;
+pub mod proxy;
+pub mod sessions;
+pub mod live;
+pub mod export;
+pub mod filter;
+pub mod error_tracking;
+pub mod telemetry;
+pub mod writer;
 
 pub use request_logger::{RequestLogger, ClickLog, SystemPerformanceLog};
-pub use performance::PerformanceTracker;
-pub use db_logger::LoggerDb;
\ No newline at end of file
+pub use store::LogStore;
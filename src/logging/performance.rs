@@ -1,45 +1,181 @@
 // src/logging/performance.rs
-use std::time::Instant;
-use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use chrono::Utc;
+use lazy_static::lazy_static;
 use mongodb::bson::DateTime as BsonDateTime;
-use super::SystemPerformanceLog;
+use super::{LogStore, SystemPerformanceLog};
+use tracing::error;
 
-#[derive(Debug, Clone)]
-pub struct PerformanceTracker {
-    start_time: Instant,
+/// Open HTTP connections, tracked through `ConnectionGuard`
+pub static ACTIVE_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+/// Requests currently being handled, tracked through `InFlightGuard`
+pub static IN_FLIGHT_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+/// Response time accumulated since the last system sample
+static RESPONSE_TIME_TOTAL_MS: AtomicU64 = AtomicU64::new(0);
+static RESPONSE_COUNT: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    /// Rate of the clock ticks /proc reports CPU times in
+    pub static ref CLOCK_TICKS_PER_SEC: f64 = {
+        // SAFETY: sysconf only reads a system configuration value
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 { ticks as f64 } else { 100.0 }
+    };
+}
+
+/// Record a finished response so the next system sample can report the average render time
+pub fn record_response(elapsed_ms: u64) {
+    RESPONSE_TIME_TOTAL_MS.fetch_add(elapsed_ms, Ordering::Relaxed);
+    RESPONSE_COUNT.fetch_add(1, Ordering::Relaxed);
 }
 
-impl PerformanceTracker {
+/// Counts an open connection for as long as it is alive (stored in connection extensions)
+pub struct ConnectionGuard;
+
+impl ConnectionGuard {
     pub fn new() -> Self {
-        PerformanceTracker {
-            start_time: Instant::now(),
-        }
+        ACTIVE_CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        ACTIVE_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a request as in flight until the guard is dropped
+pub struct InFlightGuard;
+
+impl InFlightGuard {
+    pub fn new() -> Self {
+        IN_FLIGHT_REQUESTS.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT_REQUESTS.fetch_sub(1, Ordering::Relaxed);
     }
+}
+
+/// Samples process memory and CPU usage from /proc
+pub struct SystemSampler {
+    last_cpu_ticks: Option<u64>,
+    last_sample: Instant,
+}
 
-    pub fn elapsed_ms(&self) -> u64 {
-        self.start_time.elapsed().as_millis() as u64
+impl SystemSampler {
+    pub fn new() -> Self {
+        SystemSampler {
+            last_cpu_ticks: read_cpu_ticks(),
+            last_sample: Instant::now(),
+        }
     }
-    pub fn create_system_log(
-        render_time_ms: f64,
-        active_connections: usize,
-        request_queue_depth: usize,
-    ) -> SystemPerformanceLog {
+
+    /// Take a sample; CPU percent is averaged over the time since the previous sample
+    pub fn sample(&mut self) -> SystemPerformanceLog {
+        let now = Instant::now();
+        let cpu_ticks = read_cpu_ticks();
+        let wall_secs = now.duration_since(self.last_sample).as_secs_f64();
+
+        let cpu_usage_percent = match (self.last_cpu_ticks, cpu_ticks) {
+            (Some(prev), Some(current)) if wall_secs > 0.0 => {
+                (current.saturating_sub(prev) as f64 / *CLOCK_TICKS_PER_SEC) / wall_secs * 100.0
+            }
+            _ => 0.0,
+        };
+
+        self.last_cpu_ticks = cpu_ticks;
+        self.last_sample = now;
+
+        let total_ms = RESPONSE_TIME_TOTAL_MS.swap(0, Ordering::Relaxed);
+        let count = RESPONSE_COUNT.swap(0, Ordering::Relaxed);
+        let render_time_ms = if count > 0 { total_ms as f64 / count as f64 } else { 0.0 };
+
         SystemPerformanceLog {
             id: None,
             timestamp: BsonDateTime::from_millis(Utc::now().timestamp_millis()),
             render_time_ms,
-            memory_usage_mb: 0.0,
-            cpu_usage_percent: 0.0,
-            active_connections,
-            request_queue_depth,
+            memory_usage_mb: read_rss_mb().unwrap_or(0.0),
+            cpu_usage_percent,
+            active_connections: ACTIVE_CONNECTIONS.load(Ordering::Relaxed),
+            request_queue_depth: IN_FLIGHT_REQUESTS.load(Ordering::Relaxed),
         }
     }
 }
 
-
-impl Default for PerformanceTracker {
+impl Default for SystemSampler {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Resident set size of this process in MB, from /proc/self/status
+pub fn read_rss_mb() -> Option<f64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_rss_kb(&status).map(|kb| kb as f64 / 1024.0)
+}
+
+/// User + system CPU time of this process in clock ticks, from /proc/self/stat
+pub fn read_cpu_ticks() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    parse_cpu_ticks(&stat)
+}
+
+fn parse_rss_kb(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find(|line| line.starts_with("VmRSS:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse().ok())
+}
+
+fn parse_cpu_ticks(stat: &str) -> Option<u64> {
+    // The command name may contain spaces, so fields are counted after the closing paren
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Spawn the background task that periodically writes a `SystemPerformanceLog`
+pub fn spawn_performance_sampler(logger_db: Arc<dyn LogStore>, interval: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut sampler = SystemSampler::new();
+        let mut ticker = actix_web::rt::time::interval(interval);
+        // The first tick fires immediately; skip it so the first CPU sample spans a full interval
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            if let Err(e) = logger_db.log_performance(sampler.sample()).await {
+                error!(error = %e, "Failed to log performance sample");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let status = "Name:\tportfolio\nVmPeak:\t  20000 kB\nVmRSS:\t   10240 kB\nThreads:\t4\n";
+        assert_eq!(parse_rss_kb(status), Some(10240));
+
+        let stat = "1234 (my (odd) name) S 1 1234 1234 0 -1 4194560 500 0 0 0 150 50 0 0 20 0 4 0";
+        assert_eq!(parse_cpu_ticks(stat), Some(200));
+    }
+
+    #[test]
+    fn test_clock_ticks_per_sec() {
+        // USER_HZ, which is 100 on every mainstream Linux build
+        assert_eq!(*CLOCK_TICKS_PER_SEC, 100.0);
+    }
+}
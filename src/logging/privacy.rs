@@ -0,0 +1,157 @@
+// src/logging/privacy.rs
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use chrono::{NaiveDate, Utc};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use crate::config::PRIVACY_MODE;
+
+/// How client IPs are stored in request and click logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyMode {
+    /// Store the full IP address
+    Off,
+    /// Zero the last IPv4 octet, keep only the /48 of IPv6 addresses
+    Truncate,
+    /// Salted SHA-256 with a random salt that rotates every UTC day and is never persisted
+    Hash,
+}
+
+impl FromStr for PrivacyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "" => Ok(PrivacyMode::Off),
+            "truncate" => Ok(PrivacyMode::Truncate),
+            "hash" => Ok(PrivacyMode::Hash),
+            other => Err(format!("unknown privacy mode '{}' (expected off, truncate or hash)", other)),
+        }
+    }
+}
+
+lazy_static! {
+    static ref DAILY_SALT: Mutex<(NaiveDate, [u8; 16])> = Mutex::new(new_salt());
+}
+
+fn new_salt() -> (NaiveDate, [u8; 16]) {
+    (Utc::now().date_naive(), *uuid::Uuid::new_v4().as_bytes())
+}
+
+/// Apply the configured privacy mode to a client IP
+pub fn anonymize_ip(ip: &str) -> String {
+    match *PRIVACY_MODE {
+        PrivacyMode::Off => ip.to_string(),
+        PrivacyMode::Truncate => truncate_ip(ip),
+        PrivacyMode::Hash => hash_ip(ip, &current_salt()),
+    }
+}
+
+fn current_salt() -> [u8; 16] {
+    let mut salt = DAILY_SALT.lock().unwrap_or_else(|e| e.into_inner());
+    if salt.0 != Utc::now().date_naive() {
+        *salt = new_salt();
+    }
+    salt.1
+}
+
+/// Why one visitor's logs can't be erased by IP under `mode`, if they can't. Logs are erased by
+/// the exact stored `ip_address`, and only `Off` stores something that identifies one visitor.
+pub fn erasure_blocked_by(mode: PrivacyMode) -> Option<&'static str> {
+    match mode {
+        PrivacyMode::Off => None,
+        PrivacyMode::Truncate => Some(
+            "IPs are stored truncated (privacy.mode = truncate), so the stored address is shared by \
+             every visitor in the same network and erasing it would delete other visitors' logs",
+        ),
+        PrivacyMode::Hash => Some(
+            "IPs are stored hashed with a daily salt that is never kept (privacy.mode = hash), so \
+             stored logs can't be linked back to an IP and hold none to erase",
+        ),
+    }
+}
+
+/// Cookieless visitor identifier from the raw IP and user agent.
+/// It uses the daily salt, so the same person gets an unrelated id every day.
+pub fn visitor_id(ip: &str, user_agent: &str) -> String {
+    visitor_hash(ip, user_agent, Utc::now().date_naive(), &current_salt())
+}
+
+fn visitor_hash(ip: &str, user_agent: &str, day: NaiveDate, salt: &[u8]) -> String {
+    let digest = Sha256::new()
+        .chain_update(salt)
+        .chain_update(day.to_string())
+        .chain_update(ip.as_bytes())
+        .chain_update([0])
+        .chain_update(user_agent.as_bytes())
+        .finalize();
+    digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `203.0.113.42` -> `203.0.113.0`, `2001:db8:85a3::8a2e:370:7334` -> `2001:db8:85a3::`
+pub fn truncate_ip(ip: &str) -> String {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(v4)) => {
+            let [a, b, c, _] = v4.octets();
+            IpAddr::from([a, b, c, 0]).to_string()
+        }
+        Ok(IpAddr::V6(v6)) => {
+            let s = v6.segments();
+            IpAddr::from([s[0], s[1], s[2], 0, 0, 0, 0, 0]).to_string()
+        }
+        // Not an IP (e.g. "unknown"), nothing identifying to truncate
+        Err(_) => ip.to_string(),
+    }
+}
+
+pub fn hash_ip(ip: &str, salt: &[u8]) -> String {
+    let digest = Sha256::new()
+        .chain_update(salt)
+        .chain_update(ip.as_bytes())
+        .finalize();
+    let hex: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    format!("anon-{}", hex)
+}
+
+/// Whether the client asked not to be tracked via `DNT: 1` or `Sec-GPC: 1`
+pub fn has_opted_out(req: &actix_web::HttpRequest) -> bool {
+    ["DNT", "Sec-GPC"].iter().any(|header| {
+        req.headers()
+            .get(*header)
+            .and_then(|h| h.to_str().ok())
+            .is_some_and(|v| v.trim() == "1")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_ip() {
+        assert_eq!(truncate_ip("203.0.113.42"), "203.0.113.0");
+        assert_eq!(truncate_ip("2001:db8:85a3::8a2e:370:7334"), "2001:db8:85a3::");
+        assert_eq!(truncate_ip("unknown"), "unknown");
+    }
+
+    #[test]
+    fn test_hash_ip() {
+        let salt = [7u8; 16];
+        let hashed = hash_ip("203.0.113.42", &salt);
+        assert!(hashed.starts_with("anon-"));
+        assert_eq!(hashed, hash_ip("203.0.113.42", &salt));
+        assert_ne!(hashed, hash_ip("203.0.113.42", &[8u8; 16]));
+    }
+
+    #[test]
+    fn test_visitor_hash() {
+        let salt = [7u8; 16];
+        let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let id = visitor_hash("203.0.113.42", "Firefox", day, &salt);
+        assert_eq!(id.len(), 16);
+        assert_eq!(id, visitor_hash("203.0.113.42", "Firefox", day, &salt));
+        assert_ne!(id, visitor_hash("203.0.113.42", "Chrome", day, &salt));
+        assert_ne!(id, visitor_hash("203.0.113.42", "Firefox", day.succ_opt().unwrap(), &salt));
+    }
+}
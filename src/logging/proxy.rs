@@ -0,0 +1,156 @@
+// src/logging/proxy.rs
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use actix_web::http::header::HeaderMap;
+use ipnet::IpNet;
+
+/// Reverse proxies whose forwarding headers (`Forwarded`, `X-Forwarded-For`, `X-Real-IP`) are believed
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl FromStr for TrustedProxies {
+    type Err = String;
+
+    /// Comma-separated CIDRs or plain addresses, e.g. `127.0.0.1, 10.0.0.0/8, ::1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let networks = s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry.parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| format!("invalid trusted proxy '{}'", entry))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TrustedProxies { networks })
+    }
+}
+
+impl TrustedProxies {
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.networks.iter().any(|net| net.contains(ip))
+    }
+
+    /// Resolve the originating client address.
+    /// Forwarding headers are only read when the direct peer is trusted, and the hop chain is walked
+    /// right to left so a client can't spoof its address by prepending entries.
+    pub fn resolve_client_ip(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let peer = peer?;
+        if !self.is_trusted(&peer) {
+            return Some(peer);
+        }
+
+        let hops = forwarded_hops(headers);
+        if hops.is_empty() {
+            let real_ip = headers
+                .get("X-Real-IP")
+                .and_then(|h| h.to_str().ok())
+                .and_then(parse_hop);
+            return Some(real_ip.unwrap_or(peer));
+        }
+
+        // Leftmost hop if every entry in the chain is one of our proxies
+        let client = hops.iter().rev().find(|hop| !self.is_trusted(hop)).or(hops.first());
+        client.copied().or(Some(peer))
+    }
+}
+
+/// Hop addresses from `Forwarded` (RFC 7239), falling back to `X-Forwarded-For`, in client-to-proxy order.
+/// Unparseable entries (e.g. obfuscated identifiers) end the chain since nothing left of them can be trusted.
+fn forwarded_hops(headers: &HeaderMap) -> Vec<IpAddr> {
+    let forwarded: Vec<String> = headers
+        .get_all("Forwarded")
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                key.trim().eq_ignore_ascii_case("for").then(|| value.trim().to_string())
+            })
+        })
+        .collect();
+
+    let entries: Vec<String> = if !forwarded.is_empty() {
+        forwarded
+    } else {
+        headers
+            .get_all("X-Forwarded-For")
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .map(|entry| entry.trim().to_string())
+            .collect()
+    };
+
+    let mut hops = Vec::new();
+    for entry in entries.iter().rev() {
+        match parse_hop(entry) {
+            Some(ip) => hops.push(ip),
+            None => break,
+        }
+    }
+    hops.reverse();
+    hops
+}
+
+/// Parse `1.2.3.4`, `1.2.3.4:80`, `"[2001:db8::1]:4711"` or `2001:db8::1`
+fn parse_hop(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+    value.parse::<IpAddr>().ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| value.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(
+                HeaderName::from_str(name).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        map
+    }
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let proxies: TrustedProxies = "127.0.0.1".parse().unwrap();
+        let spoofed = headers(&[("X-Forwarded-For", "1.1.1.1")]);
+        assert_eq!(proxies.resolve_client_ip(ip("203.0.113.9"), &spoofed), ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn test_trusted_peer_walks_chain() {
+        let proxies: TrustedProxies = "127.0.0.1, 10.0.0.0/8".parse().unwrap();
+
+        // The client prepended a fake entry; the first untrusted hop from the right wins
+        let xff = headers(&[("X-Forwarded-For", "1.1.1.1, 198.51.100.7, 10.0.0.2")]);
+        assert_eq!(proxies.resolve_client_ip(ip("127.0.0.1"), &xff), ip("198.51.100.7"));
+
+        let forwarded = headers(&[("Forwarded", r#"for="[2001:db8:cafe::17]:4711";proto=https, for=10.0.0.2"#)]);
+        assert_eq!(proxies.resolve_client_ip(ip("127.0.0.1"), &forwarded), ip("2001:db8:cafe::17"));
+
+        let real_ip = headers(&[("X-Real-IP", "198.51.100.8")]);
+        assert_eq!(proxies.resolve_client_ip(ip("127.0.0.1"), &real_ip), ip("198.51.100.8"));
+
+        assert_eq!(proxies.resolve_client_ip(ip("127.0.0.1"), &HeaderMap::new()), ip("127.0.0.1"));
+    }
+
+    #[test]
+    fn test_invalid_proxy_list() {
+        assert!("10.0.0.0/33".parse::<TrustedProxies>().is_err());
+        assert!("".parse::<TrustedProxies>().unwrap().networks.is_empty());
+    }
+}
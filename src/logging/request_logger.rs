@@ -1,5 +1,7 @@
 use serde::{Deserialize, Deserializer, Serialize};
 use mongodb::bson::oid::ObjectId;
+use crate::config::TRUSTED_PROXIES;
+use super::user_agent::UserAgentInfo;
 
 // Custom deserializer to handle both string and DateTime formats
 fn deserialize_flexible_datetime<'de, D>(deserializer: D) -> Result<mongodb::bson::DateTime, D::Error>
@@ -35,14 +37,52 @@ pub struct RequestLog {
     pub timestamp: mongodb::bson::DateTime,
     pub request_id: String,
     pub ip_address: String,
+    /// Daily hash of IP and user agent, used to group requests into sessions
+    #[serde(default)]
+    pub visitor_id: Option<String>,
     pub user_agent: String,
+    #[serde(default)]
+    pub user_agent_info: Option<UserAgentInfo>,
     pub method: String,
     pub path: String,
+    #[serde(default)]
+    pub route: Option<String>,
     pub status_code: u16,
     pub response_time_ms: u64,
     pub location: Option<String>,
     pub country: Option<String>,
     pub city: Option<String>,
+    #[serde(default)]
+    pub referrer: Option<String>,
+    #[serde(default)]
+    pub referrer_host: Option<String>,
+    #[serde(default)]
+    pub utm_source: Option<String>,
+    #[serde(default)]
+    pub utm_medium: Option<String>,
+    #[serde(default)]
+    pub utm_campaign: Option<String>,
+}
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Correlates everything logged for one request; stored in request extensions by `RequestLogging`
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Upstream ids end up in logs and headers, so only short tokens of safe characters are accepted
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// `utm_*` campaign parameters from a request's query string
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UtmParams {
+    pub source: Option<String>,
+    pub medium: Option<String>,
+    pub campaign: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,11 +120,32 @@ impl RequestLogger {
         Uuid::new_v4().to_string()
     }
 
-    pub fn extract_ip(req: &actix_web::HttpRequest) -> String {
-        req.connection_info()
-            .peer_addr()
-            .unwrap_or("unknown")
-            .to_string()
+    /// Reuse the upstream `X-Request-Id` when a trusted proxy sent a sane one, otherwise generate one
+    pub fn resolve_request_id(req: &actix_web::HttpRequest) -> String {
+        let from_trusted_proxy = req.peer_addr().is_some_and(|addr| TRUSTED_PROXIES.is_trusted(&addr.ip()));
+
+        req.headers()
+            .get(REQUEST_ID_HEADER)
+            .filter(|_| from_trusted_proxy)
+            .and_then(|h| h.to_str().ok())
+            .filter(|id| is_valid_request_id(id))
+            .map(str::to_string)
+            .unwrap_or_else(Self::create_request_id)
+    }
+
+    /// The id `RequestLogging` assigned to this request; none outside the middleware
+    pub fn request_id(req: &actix_web::HttpRequest) -> Option<String> {
+        use actix_web::HttpMessage;
+        req.extensions().get::<RequestId>().map(|id| id.0.clone())
+    }
+
+    /// Raw client IP; only for in-memory use such as GeoIP lookups, never store it directly.
+    /// Forwarding headers are honoured only when they come from `TRUSTED_PROXIES`.
+    pub fn extract_client_ip(req: &actix_web::HttpRequest) -> String {
+        TRUSTED_PROXIES
+            .resolve_client_ip(req.peer_addr().map(|addr| addr.ip()), req.headers())
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
     }
 
     pub fn extract_user_agent(req: &actix_web::HttpRequest) -> String {
@@ -102,4 +163,108 @@ impl RequestLogger {
     pub fn extract_method(req: &actix_web::HttpRequest) -> String {
         req.method().to_string()
     }
+
+    /// Raw `Referer` header, ignoring referrals from this site itself
+    pub fn extract_referrer(req: &actix_web::HttpRequest) -> Option<String> {
+        let referrer = req.headers()
+            .get("referer")
+            .and_then(|h| h.to_str().ok())?;
+
+        let own_host = req.connection_info().host().to_lowercase();
+        let own_host = own_host.split(':').next().unwrap_or_default();
+        match normalize_referrer_host(referrer) {
+            Some(host) if host == own_host.trim_start_matches("www.") => None,
+            Some(_) => Some(referrer.to_string()),
+            None => None,
+        }
+    }
+
+    pub fn extract_utm_params(req: &actix_web::HttpRequest) -> UtmParams {
+        let query = actix_web::web::Query::<std::collections::HashMap<String, String>>::from_query(req.query_string())
+            .map(|q| q.into_inner())
+            .unwrap_or_default();
+        let param = |name: &str| {
+            query.get(name)
+                .map(|v| v.trim().to_lowercase())
+                .filter(|v| !v.is_empty())
+        };
+
+        UtmParams {
+            source: param("utm_source"),
+            medium: param("utm_medium"),
+            campaign: param("utm_campaign"),
+        }
+    }
+
+    /// Matched route pattern (e.g. `/blog/{slug}`), falling back to a normalized path
+    pub fn extract_route(req: &actix_web::HttpRequest) -> String {
+        req.match_pattern()
+            .unwrap_or_else(|| normalize_route(req.path()))
+    }
+}
+
+/// Lowercased referrer host without a leading `www.`, e.g. `https://www.Google.com/search` -> `google.com`
+pub fn normalize_referrer_host(referrer: &str) -> Option<String> {
+    let url = url::Url::parse(referrer).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+/// Collapse variable path segments (ids, dates, IPs) so requests group by route
+pub fn normalize_route(path: &str) -> String {
+    let segments: Vec<&str> = path
+        .split('/')
+        .map(|segment| {
+            let is_hex = |s: &str| s.chars().all(|c| c.is_ascii_hexdigit());
+
+            let is_numeric = !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit());
+            let is_object_id = segment.len() == 24 && is_hex(segment);
+            let is_uuid = segment.len() == 36 && segment.split('-').count() == 5 && is_hex(&segment.replace('-', ""));
+
+            if is_numeric || is_object_id || is_uuid {
+                "{id}"
+            } else if chrono::NaiveDate::parse_from_str(segment, "%Y-%m-%d").is_ok() {
+                "{date}"
+            } else if segment.parse::<std::net::IpAddr>().is_ok() {
+                "{ip}"
+            } else {
+                segment
+            }
+        })
+        .collect();
+
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_request_id() {
+        assert!(is_valid_request_id("3f2c1a9e-8b7d-4c6e-9f0a-1b2c3d4e5f60"));
+        assert!(is_valid_request_id("edge:abc_123.4"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("bad id\r\nX-Injected: 1"));
+        assert!(!is_valid_request_id(&"a".repeat(129)));
+    }
+
+    #[test]
+    fn test_normalize_route() {
+        assert_eq!(normalize_route("/"), "/");
+        assert_eq!(normalize_route("/about"), "/about");
+        assert_eq!(normalize_route("/posts/42"), "/posts/{id}");
+        assert_eq!(normalize_route("/posts/507f1f77bcf86cd799439011/edit"), "/posts/{id}/edit");
+        assert_eq!(normalize_route("/logs/date/2024-01-15/details"), "/logs/date/{date}/details");
+        assert_eq!(normalize_route("/logs/ip/10.0.0.1"), "/logs/ip/{ip}");
+        assert_eq!(normalize_route("/blog/{slug}"), "/blog/{slug}");
+    }
+
+    #[test]
+    fn test_normalize_referrer_host() {
+        assert_eq!(normalize_referrer_host("https://www.Google.com/search?q=rust"), Some("google.com".to_string()));
+        assert_eq!(normalize_referrer_host("http://news.ycombinator.com:443/item"), Some("news.ycombinator.com".to_string()));
+        assert_eq!(normalize_referrer_host("android-app://com.slack"), Some("com.slack".to_string()));
+        assert_eq!(normalize_referrer_host("not a url"), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+// src/logging/rollup.rs
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{NaiveDate, Utc};
+use super::LogStore;
+use super::stats::LatencyPercentiles;
+use super::sessions::SessionStats;
+use tracing::{error, info};
+
+/// Pre-aggregated statistics for a single day, stored in `daily_stats`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyStats {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub date: String, // Format: "2024-01-15"
+    pub total_requests: u64,
+    pub unique_visitors: u64,
+    pub total_clicks: u64,
+    pub avg_response_time: f64,
+    pub latency: LatencyPercentiles,
+    pub status_classes: StatusClasses,
+    pub top_paths: Vec<PathCount>,
+    pub hourly: Vec<HourlyStats>,
+    #[serde(default)]
+    pub countries: Vec<BreakdownCount>,
+    #[serde(default)]
+    pub browsers: Vec<BreakdownCount>,
+    #[serde(default)]
+    pub operating_systems: Vec<BreakdownCount>,
+    #[serde(default)]
+    pub device_types: Vec<BreakdownCount>,
+    #[serde(default)]
+    pub bots: Vec<BreakdownCount>,
+    #[serde(default)]
+    pub sessions: SessionStats,
+    /// Whether requests from bots were left out of this rollup
+    #[serde(default)]
+    pub excludes_bots: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusClasses {
+    pub success: u64,      // 2xx
+    pub redirect: u64,     // 3xx
+    pub client_error: u64, // 4xx
+    pub server_error: u64, // 5xx
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourlyStats {
+    pub hour: u32,
+    pub total_requests: u64,
+    pub unique_visitors: u64,
+    pub total_clicks: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PathCount {
+    pub path: String,
+    pub count: u64,
+}
+
+/// Requests and visitors for one value of a breakdown (country, browser, OS, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakdownCount {
+    pub name: String,
+    pub requests: u64,
+    pub unique_visitors: u64,
+}
+
+impl DailyStats {
+    pub fn is_empty(&self) -> bool {
+        self.total_requests == 0 && self.total_clicks == 0
+    }
+}
+
+/// Sum a breakdown across several days, busiest first.
+/// Visitors are counted once per day, so a returning visitor counts again on each new day.
+pub fn merge_breakdown<'a>(
+    days: impl IntoIterator<Item = &'a DailyStats>,
+    breakdown: impl Fn(&'a DailyStats) -> &'a Vec<BreakdownCount>,
+) -> Vec<BreakdownCount> {
+    let mut merged: Vec<BreakdownCount> = Vec::new();
+
+    for entry in days.into_iter().flat_map(|day| breakdown(day).iter()) {
+        match merged.iter_mut().find(|m| m.name == entry.name) {
+            Some(existing) => {
+                existing.requests += entry.requests;
+                existing.unique_visitors += entry.unique_visitors;
+            }
+            None => merged.push(entry.clone()),
+        }
+    }
+
+    merged.sort_by_key(|m| std::cmp::Reverse(m.unique_visitors));
+    merged
+}
+
+/// Materialize rollups for every finished day that hasn't been rolled up yet.
+/// The most recent rollup is always recomputed to pick up late writes.
+pub async fn rollup_pending_days(
+    logger_db: &dyn LogStore,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let today = Utc::now().date_naive();
+
+    let start = match logger_db.get_latest_daily_stats_date().await? {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d")?,
+        None => match logger_db.get_earliest_log_date().await? {
+            Some(date) => date,
+            None => return Ok(0),
+        },
+    };
+
+    let mut rolled_up = 0;
+    for day in start.iter_days().take_while(|day| *day < today) {
+        // Both variants are stored so the bot toggle never needs a live aggregation
+        for exclude_bots in [false, true] {
+            let stats = logger_db.compute_daily_stats(day, exclude_bots).await?;
+            if stats.is_empty() {
+                continue;
+            }
+            logger_db.upsert_daily_stats(&stats).await?;
+        }
+        rolled_up += 1;
+    }
+
+    Ok(rolled_up)
+}
+
+/// Recompute the rollups of `days` after some of their logs were deleted, so the dashboard stops
+/// counting them. Days that haven't been rolled up yet are left to the rollup job.
+pub async fn refresh_days(
+    logger_db: &dyn LogStore,
+    days: impl IntoIterator<Item = NaiveDate>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(latest) = logger_db.get_latest_daily_stats_date().await? else {
+        return Ok(());
+    };
+    let latest = NaiveDate::parse_from_str(&latest, "%Y-%m-%d")?;
+
+    for day in days.into_iter().filter(|day| *day <= latest) {
+        logger_db.delete_daily_stats(&day.format("%Y-%m-%d").to_string()).await?;
+        for exclude_bots in [false, true] {
+            let stats = logger_db.compute_daily_stats(day, exclude_bots).await?;
+            if !stats.is_empty() {
+                logger_db.upsert_daily_stats(&stats).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Spawn the background job that keeps `daily_stats` up to date
+pub fn spawn_rollup_job(logger_db: Arc<dyn LogStore>, interval: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match rollup_pending_days(&*logger_db).await {
+                Ok(count) if count > 0 => info!(days = count, "Rolled up daily stats"),
+                Ok(_) => {}
+                Err(e) => error!(error = %e, "Failed to roll up daily stats"),
+            }
+        }
+    });
+}
@@ -0,0 +1,152 @@
+// src/logging/sessions.rs
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use super::rollup::PathCount;
+
+/// A visit ends after this much inactivity from the same visitor
+pub const SESSION_TIMEOUT_MS: i64 = 30 * 60 * 1000;
+
+/// One page view, as far as sessionization is concerned
+#[derive(Debug, Clone)]
+pub struct PageHit {
+    pub visitor_id: String,
+    pub timestamp_ms: i64,
+    pub path: String,
+}
+
+/// Visit metrics for a day (or several merged days).
+/// Sums are kept alongside the ratios so days can be merged exactly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionStats {
+    pub sessions: u64,
+    pub page_views: u64,
+    pub bounces: u64,
+    pub total_duration_secs: u64,
+    pub pages_per_session: f64,
+    pub bounce_rate: f64, // percent of sessions with a single page view
+    pub avg_duration_secs: f64,
+    pub entry_pages: Vec<PathCount>,
+    pub exit_pages: Vec<PathCount>,
+}
+
+impl SessionStats {
+    /// Split page hits into sessions per visitor, using `SESSION_TIMEOUT_MS` of inactivity as the boundary
+    pub fn from_hits(mut hits: Vec<PageHit>) -> Self {
+        hits.sort_by(|a, b| a.visitor_id.cmp(&b.visitor_id).then(a.timestamp_ms.cmp(&b.timestamp_ms)));
+
+        let mut stats = SessionStats::default();
+        let mut entry_pages: HashMap<String, u64> = HashMap::new();
+        let mut exit_pages: HashMap<String, u64> = HashMap::new();
+
+        let mut start = 0;
+        while start < hits.len() {
+            let mut end = start + 1;
+            while end < hits.len()
+                && hits[end].visitor_id == hits[start].visitor_id
+                && hits[end].timestamp_ms - hits[end - 1].timestamp_ms <= SESSION_TIMEOUT_MS
+            {
+                end += 1;
+            }
+
+            let session = &hits[start..end];
+            let (first, last) = (&session[0], &session[session.len() - 1]);
+            stats.sessions += 1;
+            stats.page_views += session.len() as u64;
+            if session.len() == 1 {
+                stats.bounces += 1;
+            }
+            stats.total_duration_secs += ((last.timestamp_ms - first.timestamp_ms) / 1000) as u64;
+            *entry_pages.entry(first.path.clone()).or_default() += 1;
+            *exit_pages.entry(last.path.clone()).or_default() += 1;
+
+            start = end;
+        }
+
+        stats.entry_pages = top_pages(entry_pages);
+        stats.exit_pages = top_pages(exit_pages);
+        stats.with_ratios()
+    }
+
+    /// Combine several days, summing counts and entry/exit pages
+    pub fn merge<'a>(days: impl IntoIterator<Item = &'a SessionStats>) -> Self {
+        let mut merged = SessionStats::default();
+        let mut entry_pages: HashMap<String, u64> = HashMap::new();
+        let mut exit_pages: HashMap<String, u64> = HashMap::new();
+
+        for day in days {
+            merged.sessions += day.sessions;
+            merged.page_views += day.page_views;
+            merged.bounces += day.bounces;
+            merged.total_duration_secs += day.total_duration_secs;
+            for page in &day.entry_pages {
+                *entry_pages.entry(page.path.clone()).or_default() += page.count;
+            }
+            for page in &day.exit_pages {
+                *exit_pages.entry(page.path.clone()).or_default() += page.count;
+            }
+        }
+
+        merged.entry_pages = top_pages(entry_pages);
+        merged.exit_pages = top_pages(exit_pages);
+        merged.with_ratios()
+    }
+
+    fn with_ratios(mut self) -> Self {
+        if self.sessions > 0 {
+            let sessions = self.sessions as f64;
+            self.pages_per_session = self.page_views as f64 / sessions;
+            self.bounce_rate = self.bounces as f64 / sessions * 100.0;
+            self.avg_duration_secs = self.total_duration_secs as f64 / sessions;
+        }
+        self
+    }
+}
+
+/// Ten most common pages, busiest first
+fn top_pages(counts: HashMap<String, u64>) -> Vec<PathCount> {
+    let mut pages: Vec<PathCount> = counts
+        .into_iter()
+        .map(|(path, count)| PathCount { path, count })
+        .collect();
+    pages.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+    pages.truncate(10);
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(visitor_id: &str, minute: i64, path: &str) -> PageHit {
+        PageHit {
+            visitor_id: visitor_id.to_string(),
+            timestamp_ms: minute * 60 * 1000,
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sessionize() {
+        let stats = SessionStats::from_hits(vec![
+            hit("a", 0, "/"),
+            hit("a", 10, "/projects"),
+            hit("a", 25, "/blog"),
+            // 31 minutes of inactivity starts a new session
+            hit("a", 56, "/blog"),
+            hit("b", 5, "/"),
+        ]);
+
+        assert_eq!(stats.sessions, 3);
+        assert_eq!(stats.page_views, 5);
+        assert_eq!(stats.bounces, 2);
+        assert_eq!(stats.total_duration_secs, 25 * 60);
+        assert!((stats.bounce_rate - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.entry_pages[0], PathCount { path: "/".to_string(), count: 2 });
+        assert_eq!(stats.exit_pages[0], PathCount { path: "/blog".to_string(), count: 2 });
+
+        let merged = SessionStats::merge([&stats, &stats]);
+        assert_eq!(merged.sessions, 6);
+        assert_eq!(merged.pages_per_session, stats.pages_per_session);
+        assert_eq!(merged.entry_pages[0].count, 4);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// Response time percentiles (in milliseconds) for a set of requests
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl LatencyPercentiles {
+    /// Compute percentiles from response times sorted in ascending order
+    pub fn from_sorted(sorted: &[u64]) -> Self {
+        LatencyPercentiles {
+            p50: percentile(sorted, 50.0),
+            p90: percentile(sorted, 90.0),
+            p95: percentile(sorted, 95.0),
+            p99: percentile(sorted, 99.0),
+        }
+    }
+}
+
+/// Request volume, server errors and latency over a recent window, used by alert rules
+#[derive(Debug, Clone, Default)]
+pub struct TrafficSummary {
+    pub requests: u64,
+    pub server_errors: u64,
+    pub latency: LatencyPercentiles,
+}
+
+impl TrafficSummary {
+    pub fn error_rate_percent(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.server_errors as f64 / self.requests as f64 * 100.0
+        }
+    }
+}
+
+/// Latency breakdown for a single normalized route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutePerformance {
+    pub route: String,
+    pub count: u64,
+    pub avg_response_time: f64,
+    pub max_response_time: u64,
+    pub latency: LatencyPercentiles,
+}
+
+impl RoutePerformance {
+    /// Build route stats from unsorted response times
+    pub fn from_samples(route: String, mut samples: Vec<u64>) -> Self {
+        samples.sort_unstable();
+        let count = samples.len() as u64;
+        let avg_response_time = if count > 0 {
+            samples.iter().sum::<u64>() as f64 / count as f64
+        } else {
+            0.0
+        };
+
+        RoutePerformance {
+            route,
+            count,
+            avg_response_time,
+            max_response_time: samples.last().copied().unwrap_or(0),
+            latency: LatencyPercentiles::from_sorted(&samples),
+        }
+    }
+
+    /// Fold in the stats of more requests to the same route. Percentiles can't be combined
+    /// exactly, so they are averaged weighted by request count.
+    pub fn merge(&mut self, other: RoutePerformance) {
+        let total = self.count + other.count;
+        if total == 0 {
+            return;
+        }
+        let weigh = |a: f64, b: f64| (a * self.count as f64 + b * other.count as f64) / total as f64;
+
+        self.avg_response_time = weigh(self.avg_response_time, other.avg_response_time);
+        self.latency = LatencyPercentiles {
+            p50: weigh(self.latency.p50, other.latency.p50),
+            p90: weigh(self.latency.p90, other.latency.p90),
+            p95: weigh(self.latency.p95, other.latency.p95),
+            p99: weigh(self.latency.p99, other.latency.p99),
+        };
+        self.max_response_time = self.max_response_time.max(other.max_response_time);
+        self.count = total;
+    }
+}
+
+/// Traffic attributed to one `utm_source` / `utm_medium` / `utm_campaign` combination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignCount {
+    pub source: Option<String>,
+    pub medium: Option<String>,
+    pub campaign: Option<String>,
+    pub requests: u64,
+    pub unique_visitors: u64,
+}
+
+/// Nearest-rank percentile of an ascending slice, 0.0 when empty
+pub fn percentile(sorted: &[u64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, sorted.len()) - 1;
+    sorted[index] as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&values, 50.0), 50.0);
+        assert_eq!(percentile(&values, 99.0), 99.0);
+        assert_eq!(percentile(&values, 100.0), 100.0);
+        assert_eq!(percentile(&[7], 95.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_merge_route_performance() {
+        let mut route = RoutePerformance::from_samples("/blog/{slug}".to_string(), vec![10, 20, 30]);
+        route.merge(RoutePerformance::from_samples("/blog/{slug}".to_string(), vec![100]));
+
+        assert_eq!(route.count, 4);
+        assert_eq!(route.avg_response_time, 40.0);
+        assert_eq!(route.max_response_time, 100);
+        assert_eq!(route.latency.p50, 40.0);
+    }
+}
@@ -0,0 +1,114 @@
+// src/logging/store/file.rs
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+/// Append-only files backing a `MemoryLogStore`, one per collection, holding length-prefixed BSON
+/// documents (the same layout as `mongodump` output). New records are appended; updated records
+/// are appended again and the last copy wins on load. Deletions rewrite the file, so erased data
+/// really leaves the disk.
+///
+/// All methods block; the store runs them on the blocking thread pool.
+#[derive(Debug)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+/// A change to one collection's file, with the records already encoded
+#[derive(Debug)]
+pub enum JournalWrite {
+    Append(&'static str, Vec<u8>),
+    Rewrite(&'static str, Vec<u8>),
+}
+
+/// Encode records back to back in the journal's file layout
+pub fn encode<'a, T: Serialize + 'a>(records: impl IntoIterator<Item = &'a T>) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for record in records {
+        bytes.extend(mongodb::bson::to_vec(record).map_err(io::Error::other)?);
+    }
+    Ok(bytes)
+}
+
+impl Journal {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Journal { dir: dir.to_path_buf() })
+    }
+
+    fn path(&self, collection: &str) -> PathBuf {
+        self.dir.join(format!("{}.bson", collection))
+    }
+
+    pub fn apply(&self, writes: Vec<JournalWrite>) -> io::Result<()> {
+        for write in writes {
+            match write {
+                JournalWrite::Append(collection, bytes) => self.append(collection, &bytes)?,
+                JournalWrite::Rewrite(collection, bytes) => self.rewrite(collection, &bytes)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn append(&self, collection: &str, bytes: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(self.path(collection))?;
+        file.write_all(bytes)
+    }
+
+    /// Replace a collection's file, writing to a temporary file first so a crash keeps the old one
+    fn rewrite(&self, collection: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(collection);
+        let tmp = path.with_extension("bson.tmp");
+
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(tmp, path)
+    }
+
+    /// Every record in a collection, oldest first. A record cut short by a crash is cut off the
+    /// file, so later appends start on a record boundary; records that no longer deserialize are
+    /// skipped.
+    pub fn load<T: DeserializeOwned>(&self, collection: &str) -> io::Result<Vec<T>> {
+        let path = self.path(collection);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut records = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let Some(prefix) = bytes.get(offset..offset + 4) else { break };
+            let len = i32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+            // The smallest BSON document is 5 bytes, so anything shorter isn't a record boundary
+            if len < 5 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is corrupt: invalid record length {} at byte {}", path.display(), len, offset),
+                ));
+            }
+            let end = offset + len as usize;
+            if end > bytes.len() {
+                break;
+            }
+
+            match mongodb::bson::from_slice(&bytes[offset..end]) {
+                Ok(record) => records.push(record),
+                Err(e) => warn!(collection, offset, error = %e, "Skipping unreadable record in the log store file"),
+            }
+            offset = end;
+        }
+
+        if offset < bytes.len() {
+            warn!(collection, offset, "Dropping truncated record at the end of the log store file");
+            OpenOptions::new().write(true).open(&path)?.set_len(offset as u64)?;
+        }
+
+        Ok(records)
+    }
+}
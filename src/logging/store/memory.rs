@@ -0,0 +1,814 @@
+// src/logging/store/memory.rs
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use async_trait::async_trait;
+use chrono::{NaiveDate, Timelike, Utc};
+use futures::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use crate::logging::request_logger::{normalize_route, RequestLog};
+use crate::logging::{ClickLog, SystemPerformanceLog};
+use crate::logging::rollup::{self, BreakdownCount, DailyStats, HourlyStats, PathCount, StatusClasses};
+use crate::logging::stats::{CampaignCount, LatencyPercentiles, RoutePerformance, TrafficSummary};
+use crate::logging::sessions::{PageHit, SessionStats};
+use crate::logging::export::ExportFilter;
+use crate::logging::filter::{Filter, Filterable};
+use crate::logging::error_tracking::{ErrorEvent, ErrorGroup, RECENT_REQUEST_IDS};
+use super::file::{self, Journal, JournalWrite};
+use super::{LogStore, RecordStream};
+
+const REQUESTS: &str = "request_logs";
+const CLICKS: &str = "click_logs";
+const PERFORMANCE: &str = "performance_logs";
+const DAILY_STATS: &str = "daily_stats";
+const ERROR_EVENTS: &str = "error_events";
+
+#[derive(Debug, Default)]
+struct LogData {
+    requests: Vec<RequestLog>,
+    clicks: Vec<ClickLog>,
+    performance: Vec<SystemPerformanceLog>,
+    daily_stats: Vec<DailyStats>,
+    error_groups: Vec<ErrorGroup>,
+}
+
+/// `LogStore` holding everything in process memory, optionally persisted to a `Journal`.
+///
+/// Every record stays in memory until it is deleted, so memory grows with the log history. Keep it
+/// bounded by running `purge-logs` regularly, or use the MongoDB store for long histories.
+#[derive(Debug, Default)]
+pub struct MemoryLogStore {
+    data: RwLock<LogData>,
+    /// Held for the whole of a change, so the files see changes in the same order as memory
+    journal: Option<tokio::sync::Mutex<Arc<Journal>>>,
+}
+
+/// Journal writes collected while changing the data; nothing is encoded without a journal
+struct Changes {
+    journaled: bool,
+    writes: Vec<JournalWrite>,
+}
+
+impl Changes {
+    fn append<T: serde::Serialize>(&mut self, collection: &'static str, record: &T) -> io::Result<()> {
+        if self.journaled {
+            self.writes.push(JournalWrite::Append(collection, file::encode([record])?));
+        }
+        Ok(())
+    }
+
+    fn rewrite<T: serde::Serialize>(&mut self, collection: &'static str, records: &[T]) -> io::Result<()> {
+        if self.journaled {
+            self.writes.push(JournalWrite::Rewrite(collection, file::encode(records)?));
+        }
+        Ok(())
+    }
+}
+
+fn day_of(timestamp: mongodb::bson::DateTime) -> Option<NaiveDate> {
+    chrono::DateTime::from_timestamp_millis(timestamp.timestamp_millis()).map(|time| time.date_naive())
+}
+
+fn hour_of(timestamp: mongodb::bson::DateTime) -> Option<u32> {
+    chrono::DateTime::from_timestamp_millis(timestamp.timestamp_millis()).map(|time| time.hour())
+}
+
+fn since_millis(since: chrono::DateTime<Utc>) -> i64 {
+    since.timestamp_millis()
+}
+
+
+/// Requests and unique IPs per value, most visitors first
+fn breakdown<'a>(
+    logs: &[&'a RequestLog],
+    key: impl Fn(&'a RequestLog) -> Option<&'a str>,
+) -> Vec<BreakdownCount> {
+    let mut groups: HashMap<&str, (u64, HashSet<&str>)> = HashMap::new();
+    for log in logs {
+        if let Some(name) = key(log) {
+            let group = groups.entry(name).or_default();
+            group.0 += 1;
+            group.1.insert(&log.ip_address);
+        }
+    }
+
+    let mut counts: Vec<BreakdownCount> = groups
+        .into_iter()
+        .map(|(name, (requests, ips))| BreakdownCount { name: name.to_string(), requests, unique_visitors: ips.len() as u64 })
+        .collect();
+    counts.sort_by(|a, b| b.unique_visitors.cmp(&a.unique_visitors).then(b.requests.cmp(&a.requests)).then(a.name.cmp(&b.name)));
+    counts
+}
+
+fn sorted_latencies(logs: &[&RequestLog]) -> Vec<u64> {
+    let mut samples: Vec<u64> = logs.iter().map(|log| log.response_time_ms).collect();
+    samples.sort_unstable();
+    samples
+}
+
+impl MemoryLogStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the records persisted in `dir` and keep appending to it
+    pub fn open(dir: &Path) -> std::io::Result<Self> {
+        let journal = Journal::open(dir)?;
+
+        // Updated records are appended again, so only the last copy of each counts
+        let mut daily_stats: Vec<DailyStats> = Vec::new();
+        for stats in journal.load::<DailyStats>(DAILY_STATS)? {
+            daily_stats.retain(|s| s.date != stats.date || s.excludes_bots != stats.excludes_bots);
+            daily_stats.push(stats);
+        }
+        let mut error_groups: Vec<ErrorGroup> = Vec::new();
+        for group in journal.load::<ErrorGroup>(ERROR_EVENTS)? {
+            error_groups.retain(|g| g.fingerprint != group.fingerprint);
+            error_groups.push(group);
+        }
+        journal.apply(vec![
+            JournalWrite::Rewrite(DAILY_STATS, file::encode(&daily_stats)?),
+            JournalWrite::Rewrite(ERROR_EVENTS, file::encode(&error_groups)?),
+        ])?;
+
+        let data = LogData {
+            requests: journal.load(REQUESTS)?,
+            clicks: journal.load(CLICKS)?,
+            performance: journal.load(PERFORMANCE)?,
+            daily_stats,
+            error_groups,
+        };
+
+        Ok(MemoryLogStore { data: RwLock::new(data), journal: Some(tokio::sync::Mutex::new(Arc::new(journal))) })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, LogData> {
+        self.data.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, LogData> {
+        self.data.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Change the data under its lock, then write the journaled changes on the blocking thread
+    /// pool once the lock is released
+    async fn update<R>(
+        &self,
+        change: impl FnOnce(&mut LogData, &mut Changes) -> io::Result<R>,
+    ) -> Result<R, Box<dyn std::error::Error>> {
+        let journal = match &self.journal {
+            Some(journal) => Some(journal.lock().await),
+            None => None,
+        };
+
+        let mut changes = Changes { journaled: journal.is_some(), writes: Vec::new() };
+        let result = change(&mut self.write(), &mut changes)?;
+
+        if let Some(journal) = journal {
+            let journal = Arc::clone(&journal);
+            actix_web::rt::task::spawn_blocking(move || journal.apply(changes.writes)).await??;
+        }
+        Ok(result)
+    }
+
+    /// Requests since `since`, optionally without bots
+    fn recent_requests(data: &LogData, since: chrono::DateTime<Utc>, exclude_bots: bool) -> Vec<&RequestLog> {
+        data.requests
+            .iter()
+            .filter(|log| log.timestamp.timestamp_millis() >= since_millis(since))
+            .filter(|log| !(exclude_bots && log.is_bot()))
+            .collect()
+    }
+}
+
+#[async_trait(?Send)]
+impl LogStore for MemoryLogStore {
+    async fn log_request(&self, mut log: RequestLog) -> Result<(), Box<dyn std::error::Error>> {
+        log.id.get_or_insert_with(ObjectId::new);
+        self.update(|data, changes| {
+            changes.append(REQUESTS, &log)?;
+            data.requests.push(log);
+            Ok(())
+        }).await
+    }
+
+    async fn log_performance(&self, mut log: SystemPerformanceLog) -> Result<(), Box<dyn std::error::Error>> {
+        log.id.get_or_insert_with(ObjectId::new);
+        self.update(|data, changes| {
+            changes.append(PERFORMANCE, &log)?;
+            data.performance.push(log);
+            Ok(())
+        }).await
+    }
+
+    async fn get_request_stats_by_date(&self, day: NaiveDate) -> Result<Document, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let logs: Vec<&RequestLog> = data.requests.iter().filter(|log| day_of(log.timestamp) == Some(day)).collect();
+        if logs.is_empty() {
+            return Ok(doc! {});
+        }
+
+        let unique_ips: HashSet<&str> = logs.iter().map(|log| log.ip_address.as_str()).collect();
+        let avg = logs.iter().map(|log| log.response_time_ms as f64).sum::<f64>() / logs.len() as f64;
+        Ok(doc! {
+            "_id": Bson::Null,
+            "total_requests": logs.len() as i32,
+            "avg_response_time": avg,
+            "unique_ips": unique_ips.into_iter().collect::<Vec<_>>(),
+        })
+    }
+
+    async fn get_click_stats(&self, ip: Option<&str>) -> Result<Vec<Document>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut groups: HashMap<&str, (i32, HashSet<&str>)> = HashMap::new();
+        for click in data.clicks.iter().filter(|click| ip.is_none_or(|ip| click.ip_address == ip)) {
+            let group = groups.entry(&click.element).or_default();
+            group.0 += 1;
+            group.1.insert(&click.ip_address);
+        }
+
+        let mut results: Vec<(&str, i32, HashSet<&str>)> =
+            groups.into_iter().map(|(element, (count, ips))| (element, count, ips)).collect();
+        results.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        Ok(results
+            .into_iter()
+            .map(|(element, count, ips)| doc! {
+                "_id": element,
+                "click_count": count,
+                "unique_users": ips.into_iter().collect::<Vec<_>>(),
+            })
+            .collect())
+    }
+
+    async fn get_requests_by_ip(&self, ip: &str) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        Ok(self.read().requests.iter().filter(|log| log.ip_address == ip).cloned().collect())
+    }
+
+    async fn get_total_requests_by_day(&self) -> Result<Vec<Document>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut days: BTreeMap<NaiveDate, (i32, u64)> = BTreeMap::new();
+        for log in &data.requests {
+            if let Some(day) = day_of(log.timestamp) {
+                let entry = days.entry(day).or_default();
+                entry.0 += 1;
+                entry.1 += log.response_time_ms;
+            }
+        }
+
+        Ok(days
+            .into_iter()
+            .map(|(day, (count, total_ms))| doc! {
+                "_id": day.format("%Y-%m-%d").to_string(),
+                "total_requests": count,
+                "avg_response_time": total_ms as f64 / count as f64,
+            })
+            .collect())
+    }
+
+    async fn get_total_request_count(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        Ok(self.read().requests.len() as u64)
+    }
+
+    async fn get_all_requests(&self, limit: Option<i64>, skip: Option<u64>) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut logs: Vec<&RequestLog> = data.requests.iter().collect();
+        logs.sort_by_key(|record| Reverse(record.timestamp));
+
+        let limit = limit.filter(|limit| *limit > 0).map_or(usize::MAX, |limit| limit as usize);
+        Ok(logs.into_iter().skip(skip.unwrap_or(0) as usize).take(limit).cloned().collect())
+    }
+
+    async fn delete_requests_by_date(&self, day: NaiveDate) -> Result<u64, Box<dyn std::error::Error>> {
+        let deleted = self.update(|data, changes| {
+            let before = data.requests.len();
+            data.requests.retain(|log| day_of(log.timestamp) != Some(day));
+            changes.rewrite(REQUESTS, &data.requests)?;
+            Ok((before - data.requests.len()) as u64)
+        }).await?;
+
+        rollup::refresh_days(self, [day]).await?;
+        Ok(deleted)
+    }
+
+    async fn search_requests(&self, filter: &Filter, limit: i64, skip: u64) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut logs: Vec<&RequestLog> = data.requests
+            .iter()
+            .filter(|log| filter.matches(*log))
+            .collect();
+        logs.sort_by_key(|record| Reverse(record.timestamp));
+
+        Ok(logs.into_iter().skip(skip as usize).take(limit.max(0) as usize).cloned().collect())
+    }
+
+    async fn count_matching_requests(&self, filter: &Filter) -> Result<u64, Box<dyn std::error::Error>> {
+        let data = self.read();
+        Ok(data.requests
+            .iter()
+            .filter(|log| filter.matches(*log))
+            .count() as u64)
+    }
+
+    async fn get_requests_by_date(&self, day: NaiveDate) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut logs: Vec<RequestLog> = data.requests.iter().filter(|log| day_of(log.timestamp) == Some(day)).cloned().collect();
+        logs.sort_by_key(|record| Reverse(record.timestamp));
+        Ok(logs)
+    }
+
+    async fn compute_daily_stats(&self, day: NaiveDate, exclude_bots: bool) -> Result<DailyStats, Box<dyn std::error::Error>> {
+        let mut stats = DailyStats {
+            date: day.format("%Y-%m-%d").to_string(),
+            excludes_bots: exclude_bots,
+            ..Default::default()
+        };
+        let mut hourly: Vec<HourlyStats> = (0..24)
+            .map(|hour| HourlyStats { hour, total_requests: 0, unique_visitors: 0, total_clicks: 0 })
+            .collect();
+
+        {
+            let data = self.read();
+            let logs: Vec<&RequestLog> = data.requests
+                .iter()
+                .filter(|log| day_of(log.timestamp) == Some(day))
+                .filter(|log| !(exclude_bots && log.is_bot()))
+                .collect();
+
+            stats.total_requests = logs.len() as u64;
+            stats.unique_visitors = logs.iter().map(|log| log.ip_address.as_str()).collect::<HashSet<_>>().len() as u64;
+            if !logs.is_empty() {
+                stats.avg_response_time = logs.iter().map(|log| log.response_time_ms as f64).sum::<f64>() / logs.len() as f64;
+            }
+
+            let mut status_classes = StatusClasses::default();
+            let mut hourly_ips: Vec<HashSet<&str>> = vec![HashSet::new(); 24];
+            let mut paths: HashMap<&str, u64> = HashMap::new();
+            for log in &logs {
+                match log.status_code / 100 {
+                    2 => status_classes.success += 1,
+                    3 => status_classes.redirect += 1,
+                    4 => status_classes.client_error += 1,
+                    5 => status_classes.server_error += 1,
+                    _ => {}
+                }
+                if let Some(hour) = hour_of(log.timestamp) {
+                    hourly[hour as usize].total_requests += 1;
+                    hourly_ips[hour as usize].insert(&log.ip_address);
+                }
+                *paths.entry(&log.path).or_default() += 1;
+            }
+            stats.status_classes = status_classes;
+            for (slot, ips) in hourly.iter_mut().zip(hourly_ips) {
+                slot.unique_visitors = ips.len() as u64;
+            }
+
+            let mut top_paths: Vec<PathCount> = paths
+                .into_iter()
+                .map(|(path, count)| PathCount { path: path.to_string(), count })
+                .collect();
+            top_paths.sort_by(|a, b| b.count.cmp(&a.count).then(a.path.cmp(&b.path)));
+            top_paths.truncate(10);
+            stats.top_paths = top_paths;
+
+            stats.latency = LatencyPercentiles::from_sorted(&sorted_latencies(&logs));
+            stats.countries = breakdown(&logs, |log| log.country.as_deref());
+            stats.browsers = breakdown(&logs, |log| log.user_agent_info.as_ref().map(|info| info.browser.as_str()));
+            stats.operating_systems = breakdown(&logs, |log| log.user_agent_info.as_ref().map(|info| info.os.as_str()));
+            stats.device_types = breakdown(&logs, |log| log.user_agent_info.as_ref().map(|info| info.device_type.as_str()));
+            stats.bots = breakdown(&logs, |log| log.user_agent_info.as_ref().and_then(|info| info.bot_name.as_deref()));
+
+            for click in data.clicks.iter().filter(|click| day_of(click.timestamp) == Some(day)) {
+                stats.total_clicks += 1;
+                if let Some(hour) = hour_of(click.timestamp) {
+                    hourly[hour as usize].total_clicks += 1;
+                }
+            }
+        }
+
+        stats.hourly = hourly;
+        stats.sessions = SessionStats::from_hits(self.get_page_hits(day, exclude_bots).await?);
+        Ok(stats)
+    }
+
+    async fn export_requests(&self, filter: &ExportFilter) -> Result<RecordStream<RequestLog>, Box<dyn std::error::Error>> {
+        let filter = filter.request_filter();
+        let mut logs: Vec<RequestLog> = self.read().requests
+            .iter()
+            .filter(|log| filter.matches(*log))
+            .cloned()
+            .collect();
+        logs.sort_by_key(|record| record.timestamp);
+        Ok(futures::stream::iter(logs.into_iter().map(Ok)).boxed_local())
+    }
+
+    async fn export_clicks(&self, filter: &ExportFilter) -> Result<RecordStream<ClickLog>, Box<dyn std::error::Error>> {
+        let filter = filter.click_filter();
+        let mut clicks: Vec<ClickLog> = self.read().clicks
+            .iter()
+            .filter(|click| filter.matches(*click))
+            .cloned()
+            .collect();
+        clicks.sort_by_key(|record| record.timestamp);
+        Ok(futures::stream::iter(clicks.into_iter().map(Ok)).boxed_local())
+    }
+
+    async fn get_page_hits(&self, day: NaiveDate, exclude_bots: bool) -> Result<Vec<PageHit>, Box<dyn std::error::Error>> {
+        Ok(self.read().requests
+            .iter()
+            .filter(|log| day_of(log.timestamp) == Some(day))
+            .filter(|log| !(exclude_bots && log.is_bot()))
+            .filter(|log| log.method == "GET" && log.status_code < 400)
+            .map(|log| PageHit {
+                visitor_id: log.visitor_id.clone().unwrap_or_else(|| format!("{}|{}", log.ip_address, log.user_agent)),
+                timestamp_ms: log.timestamp.timestamp_millis(),
+                path: log.path.clone(),
+            })
+            .collect())
+    }
+
+    async fn upsert_daily_stats(&self, stats: &DailyStats) -> Result<(), Box<dyn std::error::Error>> {
+        self.update(|data, changes| {
+            changes.append(DAILY_STATS, stats)?;
+            match data.daily_stats.iter_mut().find(|s| s.date == stats.date && s.excludes_bots == stats.excludes_bots) {
+                Some(existing) => *existing = stats.clone(),
+                None => data.daily_stats.push(stats.clone()),
+            }
+            Ok(())
+        }).await
+    }
+
+    async fn delete_daily_stats(&self, date: &str) -> Result<u64, Box<dyn std::error::Error>> {
+        self.update(|data, changes| {
+            let before = data.daily_stats.len();
+            data.daily_stats.retain(|stats| stats.date != date);
+            changes.rewrite(DAILY_STATS, &data.daily_stats)?;
+            Ok((before - data.daily_stats.len()) as u64)
+        }).await
+    }
+
+    async fn get_daily_stats(&self, exclude_bots: bool) -> Result<Vec<DailyStats>, Box<dyn std::error::Error>> {
+        let mut stats: Vec<DailyStats> = self.read().daily_stats
+            .iter()
+            .filter(|stats| stats.excludes_bots == exclude_bots)
+            .cloned()
+            .collect();
+        stats.sort_by(|a, b| a.date.cmp(&b.date));
+        Ok(stats)
+    }
+
+    async fn get_latest_daily_stats_date(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        Ok(self.read().daily_stats.iter().map(|stats| stats.date.clone()).max())
+    }
+
+    async fn get_earliest_log_date(&self) -> Result<Option<NaiveDate>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let requests = data.requests.iter().map(|log| log.timestamp);
+        let clicks = data.clicks.iter().map(|click| click.timestamp);
+        Ok(requests.chain(clicks).min().and_then(day_of))
+    }
+
+    async fn get_route_performance(&self, since: chrono::DateTime<Utc>, exclude_bots: bool) -> Result<Vec<RoutePerformance>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut samples_by_route: HashMap<String, Vec<u64>> = HashMap::new();
+        for log in Self::recent_requests(&data, since, exclude_bots) {
+            let route = normalize_route(log.route.as_deref().unwrap_or(&log.path));
+            samples_by_route.entry(route).or_default().push(log.response_time_ms);
+        }
+
+        let mut routes: Vec<RoutePerformance> = samples_by_route
+            .into_iter()
+            .map(|(route, samples)| RoutePerformance::from_samples(route, samples))
+            .collect();
+        routes.sort_by(|a, b| b.latency.p95.total_cmp(&a.latency.p95));
+        Ok(routes)
+    }
+
+    async fn get_traffic_summary(&self, since: chrono::DateTime<Utc>) -> Result<TrafficSummary, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let logs = Self::recent_requests(&data, since, false);
+        Ok(TrafficSummary {
+            requests: logs.len() as u64,
+            server_errors: logs.iter().filter(|log| log.status_code >= 500).count() as u64,
+            latency: LatencyPercentiles::from_sorted(&sorted_latencies(&logs)),
+        })
+    }
+
+    async fn get_slowest_requests(&self, since: chrono::DateTime<Utc>, exclude_bots: bool, limit: i64) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let mut logs = Self::recent_requests(&data, since, exclude_bots);
+        logs.sort_by_key(|record| Reverse(record.response_time_ms));
+        Ok(logs.into_iter().take(limit.max(0) as usize).cloned().collect())
+    }
+
+    async fn get_performance_logs(&self, since: chrono::DateTime<Utc>) -> Result<Vec<SystemPerformanceLog>, Box<dyn std::error::Error>> {
+        let mut logs: Vec<SystemPerformanceLog> = self.read().performance
+            .iter()
+            .filter(|log| log.timestamp.timestamp_millis() >= since_millis(since))
+            .cloned()
+            .collect();
+        logs.sort_by_key(|record| record.timestamp);
+        Ok(logs)
+    }
+
+    async fn get_top_referrers(&self, since: chrono::DateTime<Utc>, exclude_bots: bool, limit: i64) -> Result<Vec<BreakdownCount>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        let logs = Self::recent_requests(&data, since, exclude_bots);
+        let mut referrers = breakdown(&logs, |log| log.referrer_host.as_deref());
+        referrers.truncate(limit.max(0) as usize);
+        Ok(referrers)
+    }
+
+    async fn get_campaigns(&self, since: chrono::DateTime<Utc>, exclude_bots: bool) -> Result<Vec<CampaignCount>, Box<dyn std::error::Error>> {
+        let data = self.read();
+        type CampaignKey<'a> = (Option<&'a str>, Option<&'a str>, Option<&'a str>);
+        let mut groups: HashMap<CampaignKey, (u64, HashSet<&str>)> = HashMap::new();
+        for log in Self::recent_requests(&data, since, exclude_bots) {
+            let key = (log.utm_source.as_deref(), log.utm_medium.as_deref(), log.utm_campaign.as_deref());
+            if key == (None, None, None) {
+                continue;
+            }
+            let group = groups.entry(key).or_default();
+            group.0 += 1;
+            group.1.insert(&log.ip_address);
+        }
+
+        let mut campaigns: Vec<CampaignCount> = groups
+            .into_iter()
+            .map(|((source, medium, campaign), (requests, ips))| CampaignCount {
+                source: source.map(str::to_string),
+                medium: medium.map(str::to_string),
+                campaign: campaign.map(str::to_string),
+                requests,
+                unique_visitors: ips.len() as u64,
+            })
+            .collect();
+        campaigns.sort_by(|a, b| b.unique_visitors.cmp(&a.unique_visitors).then(b.requests.cmp(&a.requests)));
+        Ok(campaigns)
+    }
+
+    async fn erase_ip(&self, ip: &str) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let (deleted, days) = self.update(|data, changes| {
+            let requests = data.requests.iter().filter(|log| log.ip_address == ip).map(|log| log.timestamp);
+            let clicks = data.clicks.iter().filter(|click| click.ip_address == ip).map(|click| click.timestamp);
+            let days: BTreeSet<NaiveDate> = requests.chain(clicks).filter_map(day_of).collect();
+
+            let (requests_before, clicks_before) = (data.requests.len(), data.clicks.len());
+            data.requests.retain(|log| log.ip_address != ip);
+            data.clicks.retain(|click| click.ip_address != ip);
+
+            changes.rewrite(REQUESTS, &data.requests)?;
+            changes.rewrite(CLICKS, &data.clicks)?;
+            Ok((((requests_before - data.requests.len()) as u64, (clicks_before - data.clicks.len()) as u64), days))
+        }).await?;
+
+        rollup::refresh_days(self, days).await?;
+        Ok(deleted)
+    }
+
+    async fn purge_before(&self, before: chrono::DateTime<Utc>) -> Result<(u64, u64, u64), Box<dyn std::error::Error>> {
+        let before = since_millis(before);
+        self.update(|data, changes| {
+            let counts = (data.requests.len(), data.clicks.len(), data.performance.len());
+            data.requests.retain(|log| log.timestamp.timestamp_millis() >= before);
+            data.clicks.retain(|click| click.timestamp.timestamp_millis() >= before);
+            data.performance.retain(|sample| sample.timestamp.timestamp_millis() >= before);
+
+            changes.rewrite(REQUESTS, &data.requests)?;
+            changes.rewrite(CLICKS, &data.clicks)?;
+            changes.rewrite(PERFORMANCE, &data.performance)?;
+            Ok((
+                (counts.0 - data.requests.len()) as u64,
+                (counts.1 - data.clicks.len()) as u64,
+                (counts.2 - data.performance.len()) as u64,
+            ))
+        }).await
+    }
+
+    async fn record_error(&self, event: ErrorEvent) -> Result<(), Box<dyn std::error::Error>> {
+        let fingerprint = event.fingerprint();
+        self.update(|data, changes| {
+            let index = match data.error_groups.iter().position(|group| group.fingerprint == fingerprint) {
+                Some(index) => index,
+                None => {
+                    data.error_groups.push(ErrorGroup {
+                        id: Some(ObjectId::new()),
+                        fingerprint,
+                        status_code: event.status_code,
+                        method: event.method.clone(),
+                        route: event.route.clone(),
+                        message: String::new(),
+                        first_seen: event.timestamp,
+                        last_seen: event.timestamp,
+                        count: 0,
+                        last_path: String::new(),
+                        last_request_id: String::new(),
+                        recent_request_ids: Vec::new(),
+                        details: None,
+                        resolved: false,
+                        resolved_at: None,
+                    });
+                    data.error_groups.len() - 1
+                }
+            };
+
+            let group = &mut data.error_groups[index];
+            group.message = event.message;
+            group.last_seen = event.timestamp;
+            group.last_path = event.path;
+            group.last_request_id = event.request_id.clone();
+            group.details = event.details;
+            group.resolved = false;
+            group.resolved_at = None;
+            group.count += 1;
+            group.recent_request_ids.push(event.request_id);
+            let excess = group.recent_request_ids.len().saturating_sub(RECENT_REQUEST_IDS as usize);
+            group.recent_request_ids.drain(..excess);
+
+            changes.append(ERROR_EVENTS, &data.error_groups[index])
+        }).await
+    }
+
+    async fn get_error_groups(&self, include_resolved: bool) -> Result<Vec<ErrorGroup>, Box<dyn std::error::Error>> {
+        let mut groups: Vec<ErrorGroup> = self.read().error_groups
+            .iter()
+            .filter(|group| include_resolved || !group.resolved)
+            .cloned()
+            .collect();
+        groups.sort_by_key(|record| Reverse(record.last_seen));
+        groups.truncate(200);
+        Ok(groups)
+    }
+
+    async fn set_error_resolved(&self, fingerprint: &str, resolved: bool) -> Result<bool, Box<dyn std::error::Error>> {
+        self.update(|data, changes| {
+            let Some(group) = data.error_groups.iter_mut().find(|group| group.fingerprint == fingerprint) else {
+                return Ok(false);
+            };
+
+            group.resolved = resolved;
+            group.resolved_at = resolved.then(mongodb::bson::DateTime::now);
+            changes.append(ERROR_EVENTS, group)?;
+            Ok(true)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::user_agent::UserAgentInfo;
+
+    fn request(timestamp_ms: i64, ip: &str, path: &str, status_code: u16, response_time_ms: u64) -> RequestLog {
+        RequestLog {
+            id: None,
+            timestamp: mongodb::bson::DateTime::from_millis(timestamp_ms),
+            request_id: format!("req-{}", timestamp_ms),
+            ip_address: ip.to_string(),
+            visitor_id: Some(format!("visitor-{}", ip)),
+            user_agent: "Mozilla/5.0".to_string(),
+            user_agent_info: Some(UserAgentInfo::parse("Mozilla/5.0 (X11; Linux x86_64) Firefox/120.0")),
+            method: "GET".to_string(),
+            path: path.to_string(),
+            route: Some(path.to_string()),
+            status_code,
+            response_time_ms,
+            location: None,
+            country: Some("NL".to_string()),
+            city: None,
+            referrer: None,
+            referrer_host: None,
+            utm_source: None,
+            utm_medium: None,
+            utm_campaign: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_journal_round_trip() {
+        let dir = std::env::temp_dir().join(format!("log-store-{}", ObjectId::new()));
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let noon = day.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp_millis();
+
+        let store = MemoryLogStore::open(&dir).unwrap();
+        store.log_request(request(noon, "1.1.1.1", "/", 200, 10)).await.unwrap();
+        store.log_request(request(noon + 1_000, "1.1.1.1", "/blog", 500, 900)).await.unwrap();
+        store.log_request(request(noon + 2_000, "2.2.2.2", "/", 200, 30)).await.unwrap();
+        store.erase_ip("2.2.2.2").await.unwrap();
+        drop(store);
+
+        let store = MemoryLogStore::open(&dir).unwrap();
+        let stats = store.compute_daily_stats(day, false).await.unwrap();
+        assert_eq!(stats.total_requests, 2);
+        assert_eq!(stats.unique_visitors, 1);
+        assert_eq!(stats.status_classes.server_error, 1);
+        assert_eq!(stats.hourly[12].total_requests, 2);
+        assert_eq!(stats.top_paths[0].count, 1);
+
+        let filter = crate::logging::filter::parse_request_filter("status:5xx").unwrap();
+        let matching = store.search_requests(&filter, 10, 0).await.unwrap();
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].path, "/blog");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_journal_recovers_from_truncated_record() {
+        let dir = std::env::temp_dir().join(format!("log-store-{}", ObjectId::new()));
+        let noon = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp_millis();
+
+        let store = MemoryLogStore::open(&dir).unwrap();
+        store.log_request(request(noon, "1.1.1.1", "/", 200, 10)).await.unwrap();
+        drop(store);
+
+        // A crash part way through appending the second record
+        let partial = file::encode([&request(noon + 1_000, "1.1.1.1", "/lost", 200, 10)]).unwrap();
+        let mut journal = std::fs::OpenOptions::new().append(true).open(dir.join("request_logs.bson")).unwrap();
+        std::io::Write::write_all(&mut journal, &partial[..partial.len() / 2]).unwrap();
+        drop(journal);
+
+        let store = MemoryLogStore::open(&dir).unwrap();
+        store.log_request(request(noon + 2_000, "2.2.2.2", "/blog", 200, 10)).await.unwrap();
+        drop(store);
+
+        let store = MemoryLogStore::open(&dir).unwrap();
+        let paths: Vec<String> = store.read().requests.iter().map(|log| log.path.clone()).collect();
+        assert_eq!(paths, ["/", "/blog"]);
+
+        // A length no record can have is corruption, not a crash
+        std::fs::write(dir.join("click_logs.bson"), [1, 0, 0, 0, 0]).unwrap();
+        assert_eq!(MemoryLogStore::open(&dir).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// The date queries agree across stores. MongoDB is only covered when `MONGODB_TEST_URI` points
+    /// at a server the test may create a scratch database on.
+    #[actix_web::test]
+    async fn test_date_queries_across_stores() {
+        let dir = std::env::temp_dir().join(format!("log-store-{}", ObjectId::new()));
+        let mongo = match std::env::var("MONGODB_TEST_URI") {
+            Ok(uri) => {
+                let config = crate::config::app::MongoConfig {
+                    uri,
+                    database: format!("log-store-{}", ObjectId::new()),
+                    migrate_on_startup: false,
+                };
+                Some(Arc::new(crate::db::MongoDb::new(&config).await.unwrap()))
+            }
+            Err(_) => None,
+        };
+
+        let mut stores: Vec<Box<dyn LogStore>> = vec![Box::new(MemoryLogStore::new()), Box::new(MemoryLogStore::open(&dir).unwrap())];
+        if let Some(db) = &mongo {
+            stores.push(Box::new(crate::logging::store::MongoLogStore::new(db)));
+        }
+
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let midnight = day.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+        let next_midnight = midnight + 24 * 60 * 60 * 1000;
+        for store in &stores {
+            store.log_request(request(midnight - 1, "1.1.1.1", "/before", 200, 10)).await.unwrap();
+            store.log_request(request(midnight, "1.1.1.1", "/first", 200, 10)).await.unwrap();
+            store.log_request(request(next_midnight - 1, "2.2.2.2", "/last", 200, 30)).await.unwrap();
+            store.log_request(request(next_midnight, "2.2.2.2", "/after", 200, 10)).await.unwrap();
+
+            let paths: Vec<String> = store.get_requests_by_date(day).await.unwrap().into_iter().map(|log| log.path).collect();
+            assert_eq!(paths, ["/last", "/first"]);
+
+            let stats = store.get_request_stats_by_date(day).await.unwrap();
+            assert_eq!(stats.get_i32("total_requests").unwrap(), 2);
+            assert_eq!(stats.get_f64("avg_response_time").unwrap(), 20.0);
+
+            assert_eq!(store.delete_requests_by_date(day).await.unwrap(), 2);
+            assert!(store.get_requests_by_date(day).await.unwrap().is_empty());
+            assert_eq!(store.get_all_requests(None, None).await.unwrap().len(), 2);
+        }
+
+        if let Some(db) = mongo {
+            db.database.drop().await.unwrap();
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_erasure_updates_rollups() {
+        let store = MemoryLogStore::new();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let noon = day.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp_millis();
+        store.log_request(request(noon, "1.1.1.1", "/", 200, 10)).await.unwrap();
+        store.log_request(request(noon + 1_000, "2.2.2.2", "/", 200, 30)).await.unwrap();
+        rollup::rollup_pending_days(&store).await.unwrap();
+
+        store.erase_ip("2.2.2.2").await.unwrap();
+        let stats = store.get_daily_stats(false).await.unwrap();
+        assert_eq!(stats[0].total_requests, 1);
+        assert_eq!(stats[0].unique_visitors, 1);
+
+        store.delete_requests_by_date(day).await.unwrap();
+        assert!(store.get_daily_stats(false).await.unwrap().is_empty());
+        assert!(store.get_daily_stats(true).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+// src/logging/store/mod.rs
+pub mod mongo;
+pub mod memory;
+pub mod file;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use futures::stream::LocalBoxStream;
+use std::ops::Bound;
+use mongodb::bson::Document;
+use crate::db::MongoDb;
+use crate::logging::request_logger::RequestLog;
+use crate::logging::{ClickLog, SystemPerformanceLog};
+use crate::logging::rollup::{BreakdownCount, DailyStats};
+use crate::logging::stats::{CampaignCount, RoutePerformance, TrafficSummary};
+use crate::logging::sessions::PageHit;
+use crate::logging::export::ExportFilter;
+use crate::logging::filter::Filter;
+use crate::logging::error_tracking::{ErrorEvent, ErrorGroup};
+
+pub use memory::MemoryLogStore;
+pub use mongo::MongoLogStore;
+
+/// Records streamed out of a store, e.g. for exports
+pub type RecordStream<T> = LocalBoxStream<'static, Result<T, Box<dyn std::error::Error>>>;
+
+/// Storage for request, click and performance logs, daily rollups and error groups.
+///
+/// Searches take a typed `Filter`, from `filter::parse_request_filter` or `ExportFilter`, which
+/// each store evaluates natively.
+#[async_trait(?Send)]
+pub trait LogStore: Send + Sync {
+    async fn log_request(&self, log: RequestLog) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn log_performance(&self, log: SystemPerformanceLog) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Totals for the requests of `day` (UTC)
+    async fn get_request_stats_by_date(&self, day: NaiveDate) -> Result<Document, Box<dyn std::error::Error>>;
+
+    /// Clicks per element, optionally for a single IP
+    async fn get_click_stats(&self, ip: Option<&str>) -> Result<Vec<Document>, Box<dyn std::error::Error>>;
+
+    async fn get_requests_by_ip(&self, ip: &str) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>>;
+
+    async fn get_total_requests_by_day(&self) -> Result<Vec<Document>, Box<dyn std::error::Error>>;
+
+    async fn get_total_request_count(&self) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// Requests newest first
+    async fn get_all_requests(&self, limit: Option<i64>, skip: Option<u64>) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>>;
+
+    /// Delete the requests of `day` (UTC) and recompute that day's rollups
+    async fn delete_requests_by_date(&self, day: NaiveDate) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// One page of requests matching a filter from `filter::parse_request_filter`, newest first
+    async fn search_requests(&self, filter: &Filter, limit: i64, skip: u64) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>>;
+
+    /// Number of requests matching a filter from `filter::parse_request_filter`
+    async fn count_matching_requests(&self, filter: &Filter) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// The requests of `day` (UTC), newest first
+    async fn get_requests_by_date(&self, day: NaiveDate) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>>;
+
+    /// Aggregate request and click logs for a single day into a `DailyStats` rollup
+    async fn compute_daily_stats(&self, day: NaiveDate, exclude_bots: bool) -> Result<DailyStats, Box<dyn std::error::Error>>;
+
+    /// Request logs matching `filter`, oldest first, for streaming exports
+    async fn export_requests(&self, filter: &ExportFilter) -> Result<RecordStream<RequestLog>, Box<dyn std::error::Error>>;
+
+    /// Click logs matching `filter`, oldest first, for streaming exports
+    async fn export_clicks(&self, filter: &ExportFilter) -> Result<RecordStream<ClickLog>, Box<dyn std::error::Error>>;
+
+    /// Successful GET requests on `day`, for sessionization.
+    /// Legacy rows without a `visitor_id` fall back to IP + user agent.
+    async fn get_page_hits(&self, day: NaiveDate, exclude_bots: bool) -> Result<Vec<PageHit>, Box<dyn std::error::Error>>;
+
+    async fn upsert_daily_stats(&self, stats: &DailyStats) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Delete both rollups (with and without bots) of `date` ("2024-01-15")
+    async fn delete_daily_stats(&self, date: &str) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// Rollups oldest first
+    async fn get_daily_stats(&self, exclude_bots: bool) -> Result<Vec<DailyStats>, Box<dyn std::error::Error>>;
+
+    async fn get_latest_daily_stats_date(&self) -> Result<Option<String>, Box<dyn std::error::Error>>;
+
+    /// Date of the oldest request or click log, used to seed the first rollup run
+    async fn get_earliest_log_date(&self) -> Result<Option<NaiveDate>, Box<dyn std::error::Error>>;
+
+    /// Per-route latency breakdown for requests since `since`, slowest (by p95) first
+    async fn get_route_performance(&self, since: chrono::DateTime<Utc>, exclude_bots: bool) -> Result<Vec<RoutePerformance>, Box<dyn std::error::Error>>;
+
+    /// Requests, 5xx responses and latency percentiles since `since`
+    async fn get_traffic_summary(&self, since: chrono::DateTime<Utc>) -> Result<TrafficSummary, Box<dyn std::error::Error>>;
+
+    /// Number of requests logged in `[from, to)`
+    async fn count_requests_between(
+        &self,
+        from: chrono::DateTime<Utc>,
+        to: chrono::DateTime<Utc>,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let filter = Filter::Time((
+            Bound::Included(mongodb::bson::DateTime::from_millis(from.timestamp_millis())),
+            Bound::Excluded(mongodb::bson::DateTime::from_millis(to.timestamp_millis())),
+        ));
+        self.count_matching_requests(&filter).await
+    }
+
+    async fn get_slowest_requests(&self, since: chrono::DateTime<Utc>, exclude_bots: bool, limit: i64) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>>;
+
+    /// Performance samples since `since`, oldest first
+    async fn get_performance_logs(&self, since: chrono::DateTime<Utc>) -> Result<Vec<SystemPerformanceLog>, Box<dyn std::error::Error>>;
+
+    /// External referrer hosts since `since`, by unique visitors
+    async fn get_top_referrers(&self, since: chrono::DateTime<Utc>, exclude_bots: bool, limit: i64) -> Result<Vec<BreakdownCount>, Box<dyn std::error::Error>>;
+
+    /// UTM campaigns since `since`, by unique visitors
+    async fn get_campaigns(&self, since: chrono::DateTime<Utc>, exclude_bots: bool) -> Result<Vec<CampaignCount>, Box<dyn std::error::Error>>;
+
+    /// Erase every request and click log stored under exactly `ip` (GDPR erasure), recomputing the
+    /// rollups of the days they appeared on
+    async fn erase_ip(&self, ip: &str) -> Result<(u64, u64), Box<dyn std::error::Error>>;
+
+    /// Delete request, click and performance logs from before `before`, returning how many of each.
+    /// Daily rollups and error groups are kept.
+    async fn purge_before(&self, before: chrono::DateTime<Utc>) -> Result<(u64, u64, u64), Box<dyn std::error::Error>>;
+
+    /// Add an error to its fingerprint group, creating the group on first sight.
+    /// A resolved group that happens again is reopened.
+    async fn record_error(&self, event: ErrorEvent) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Error groups, most recently seen first
+    async fn get_error_groups(&self, include_resolved: bool) -> Result<Vec<ErrorGroup>, Box<dyn std::error::Error>>;
+
+    /// Mark an error group resolved or reopen it; returns whether the group exists
+    async fn set_error_resolved(&self, fingerprint: &str, resolved: bool) -> Result<bool, Box<dyn std::error::Error>>;
+}
+
+/// Which `LogStore` backs analytics, from `LOG_STORE` (`mongo`, `memory` or `file`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogStoreKind {
+    /// Needs MongoDB 7.0 or newer, checked at startup
+    Mongo,
+    /// Lost on restart; for tests and throwaway instances
+    Memory,
+    /// Kept in memory and persisted to append-only files in this directory. Memory grows with the
+    /// history until `purge-logs` removes old records.
+    File(PathBuf),
+}
+
+impl FromStr for LogStoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "mongo" | "mongodb" => Ok(LogStoreKind::Mongo),
+            "memory" => Ok(LogStoreKind::Memory),
+            "file" => Ok(LogStoreKind::File(PathBuf::from("data/logs"))),
+            other => Err(format!("unknown log store '{}' (expected mongo, memory or file)", other)),
+        }
+    }
+}
+
+/// Open the configured store; file stores load their existing records up front.
+/// Only the MongoDB store uses `mongo_db`.
+pub fn open_log_store(kind: &LogStoreKind, mongo_db: Option<&Arc<MongoDb>>) -> std::io::Result<Arc<dyn LogStore>> {
+    Ok(match kind {
+        LogStoreKind::Mongo => {
+            let mongo_db = mongo_db.ok_or_else(|| std::io::Error::other("the mongo log store needs a MongoDB connection"))?;
+            Arc::new(MongoLogStore::new(mongo_db))
+        }
+        LogStoreKind::Memory => Arc::new(MemoryLogStore::new()),
+        LogStoreKind::File(dir) => Arc::new(MemoryLogStore::open(dir)?),
+    })
+}
@@ -0,0 +1,1142 @@
+use mongodb::bson::doc;
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+use crate::db::MongoDb;
+use crate::logging::request_logger::RequestLog;
+use crate::logging::{ClickLog, SystemPerformanceLog};
+use crate::logging::rollup::{self, BreakdownCount, DailyStats, HourlyStats, PathCount, StatusClasses};
+use crate::logging::request_logger::normalize_route;
+use crate::logging::stats::{CampaignCount, LatencyPercentiles, RoutePerformance, TrafficSummary};
+use crate::logging::sessions::{PageHit, SessionStats};
+use crate::logging::export::ExportFilter;
+use crate::logging::filter::{Filter, NumberField, TextField, TextMatch};
+use crate::logging::error_tracking::{ErrorEvent, ErrorGroup, RECENT_REQUEST_IDS};
+use super::{LogStore, RecordStream};
+use chrono::{NaiveDate, TimeZone, Utc};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+use futures::TryStreamExt;
+use tracing::instrument;
+
+/// Pipeline stage normalizing `timestamp` (BSON date or legacy ISO string) into `timestamp_date`
+fn timestamp_date_stage() -> mongodb::bson::Document {
+    doc! {
+        "$addFields": {
+            "timestamp_date": {
+                "$cond": [
+                    { "$eq": [{ "$type": "$timestamp" }, "date"] },
+                    "$timestamp",
+                    { "$dateFromString": { "dateString": "$timestamp" } }
+                ]
+            }
+        }
+    }
+}
+
+/// Query document for `timestamp` within `range`, stored either as a BSON date or as a legacy
+/// ISO 8601 string. Compares the stored field itself so the `timestamp` index serves the match.
+fn timestamp_document(range: &(Bound<mongodb::bson::DateTime>, Bound<mongodb::bson::DateTime>)) -> mongodb::bson::Document {
+    if let (Bound::Unbounded, Bound::Unbounded) = range {
+        return doc! {};
+    }
+
+    // Legacy string timestamps are ISO 8601, so they order correctly as strings
+    let legacy = |time: mongodb::bson::DateTime| {
+        chrono::DateTime::from_timestamp_millis(time.timestamp_millis())
+            .unwrap_or_default()
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string()
+    };
+    doc! {
+        "$or": [
+            { "timestamp": range_document(range) },
+            { "timestamp": range_document(&(range.0.map(legacy), range.1.map(legacy))) },
+        ]
+    }
+}
+
+fn bson_date(time: chrono::DateTime<Utc>) -> mongodb::bson::DateTime {
+    mongodb::bson::DateTime::from_millis(time.timestamp_millis())
+}
+
+/// Query document for `timestamp` at or after `since`
+fn since_document(since: chrono::DateTime<Utc>) -> mongodb::bson::Document {
+    timestamp_document(&(Bound::Included(bson_date(since)), Bound::Unbounded))
+}
+
+/// Query document for `timestamp` falling on `day` (UTC)
+fn day_document(day: NaiveDate) -> mongodb::bson::Document {
+    let start = Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default());
+    let end = start + chrono::Duration::days(1);
+
+    timestamp_document(&(Bound::Included(bson_date(start)), Bound::Excluded(bson_date(end))))
+}
+
+/// Pipeline stage matching documents whose timestamp falls on `day` (UTC)
+fn day_match_stage(day: NaiveDate) -> mongodb::bson::Document {
+    doc! { "$match": day_document(day) }
+}
+
+/// Pipeline stage dropping requests classified as bots when `exclude_bots` is set
+fn bot_match_stage(exclude_bots: bool) -> mongodb::bson::Document {
+    if exclude_bots {
+        doc! { "$match": { "user_agent_info.is_bot": { "$ne": true } } }
+    } else {
+        doc! { "$match": {} }
+    }
+}
+
+/// `$facet` branch counting requests and unique visitors per value of `field`
+fn breakdown_facet(field: &str) -> Vec<mongodb::bson::Document> {
+    vec![
+        doc! { "$match": { field: { "$type": "string" } } },
+        doc! {
+            "$group": {
+                "_id": format!("${}", field),
+                "requests": { "$sum": 1 },
+                "unique_ips": { "$addToSet": "$ip_address" }
+            }
+        },
+        doc! { "$project": { "requests": 1, "unique_visitors": { "$size": "$unique_ips" } } },
+        doc! { "$sort": { "unique_visitors": -1 } },
+    ]
+}
+
+fn parse_breakdown(facets: &mongodb::bson::Document, key: &str) -> Vec<BreakdownCount> {
+    facets
+        .get_array(key)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| e.as_document())
+                .map(|e| BreakdownCount {
+                    name: e.get_str("_id").unwrap_or_default().to_string(),
+                    requests: bson_number(e.get("requests")) as u64,
+                    unique_visitors: bson_number(e.get("unique_visitors")) as u64,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Query document for a typed filter
+fn filter_document(filter: &Filter) -> mongodb::bson::Document {
+    match filter {
+        Filter::And(filters) => match filters.as_slice() {
+            [] => doc! {},
+            [filter] => filter_document(filter),
+            filters => doc! { "$and": filters.iter().map(filter_document).collect::<Vec<_>>() },
+        },
+        Filter::Or(filters) if filters.is_empty() => doc! { "$expr": false },
+        Filter::Or(filters) => doc! { "$or": filters.iter().map(filter_document).collect::<Vec<_>>() },
+        Filter::Not(filter) => doc! { "$nor": [filter_document(filter)] },
+        Filter::Text(field, TextMatch::Exact(value)) => doc! { text_field_name(*field): value },
+        Filter::Text(field, TextMatch::Pattern { regex, ignore_case }) => {
+            let mut condition = doc! { "$regex": regex.as_str() };
+            if *ignore_case {
+                condition.insert("$options", "i");
+            }
+            doc! { text_field_name(*field): condition }
+        }
+        Filter::Number(NumberField::StatusCode, range) => doc! { "status_code": range_document(range) },
+        Filter::Number(NumberField::ResponseTimeMs, range) => doc! { "response_time_ms": range_document(range) },
+        Filter::Time(range) => timestamp_document(range),
+        Filter::Bot(true) => doc! { "user_agent_info.is_bot": true },
+        Filter::Bot(false) => doc! { "user_agent_info.is_bot": { "$ne": true } },
+    }
+}
+
+fn text_field_name(field: TextField) -> &'static str {
+    match field {
+        TextField::Path => "path",
+        TextField::Route => "route",
+        TextField::IpAddress => "ip_address",
+        TextField::Method => "method",
+        TextField::Country => "country",
+        TextField::Browser => "user_agent_info.browser",
+        TextField::ReferrerHost => "referrer_host",
+        TextField::VisitorId => "visitor_id",
+        TextField::PagePath => "page_path",
+        TextField::EventType => "event_type",
+    }
+}
+
+fn range_document<T: Clone + PartialEq + Into<mongodb::bson::Bson>>(range: &(Bound<T>, Bound<T>)) -> mongodb::bson::Document {
+    if let (Bound::Included(low), Bound::Included(high)) = range
+        && low == high
+    {
+        return doc! { "$eq": low.clone() };
+    }
+
+    let mut condition = mongodb::bson::Document::new();
+    match &range.0 {
+        Bound::Included(low) => { condition.insert("$gte", low.clone()); }
+        Bound::Excluded(low) => { condition.insert("$gt", low.clone()); }
+        Bound::Unbounded => {}
+    }
+    match &range.1 {
+        Bound::Included(high) => { condition.insert("$lte", high.clone()); }
+        Bound::Excluded(high) => { condition.insert("$lt", high.clone()); }
+        Bound::Unbounded => {}
+    }
+    condition
+}
+
+/// `$group` accumulator for the p50, p90, p95 and p99 response times. Computed on the server
+/// (MongoDB 7.0+), so a busy day or route never has to fit its samples into one document.
+fn latency_accumulator() -> mongodb::bson::Document {
+    doc! {
+        "$percentile": {
+            "input": "$response_time_ms",
+            "p": [0.5, 0.9, 0.95, 0.99],
+            "method": "approximate"
+        }
+    }
+}
+
+/// Read the `latency` field produced by `latency_accumulator`
+fn parse_latency(result: &mongodb::bson::Document) -> LatencyPercentiles {
+    let latency: Vec<f64> = result.get_array("latency")
+        .map(|values| values.iter().map(|v| bson_number(Some(v))).collect())
+        .unwrap_or_default();
+    match latency[..] {
+        [p50, p90, p95, p99] => LatencyPercentiles { p50, p90, p95, p99 },
+        _ => LatencyPercentiles::default(),
+    }
+}
+
+/// Read a numeric aggregation result regardless of the BSON integer/double width
+fn bson_number(value: Option<&mongodb::bson::Bson>) -> f64 {
+    use mongodb::bson::Bson;
+
+    match value {
+        Some(Bson::Int32(v)) => *v as f64,
+        Some(Bson::Int64(v)) => *v as f64,
+        Some(Bson::Double(v)) => *v,
+        _ => 0.0,
+    }
+}
+
+pub struct MongoLogStore {
+    mongo_db: Arc<MongoDb>,
+}
+
+impl MongoLogStore {
+    pub fn new(mongo_db: &Arc<MongoDb>) -> Self {
+        Self {
+            mongo_db: Arc::clone(mongo_db),
+        }
+    }
+
+    fn log_request_collection(&self) -> mongodb::Collection<RequestLog> {
+        self.mongo_db.database.collection::<RequestLog>("request_logs")
+    }
+
+    fn log_click_collection(&self) -> mongodb::Collection<ClickLog> {
+        self.mongo_db.database.collection::<ClickLog>("click_logs")
+    }
+
+    fn daily_stats_collection(&self) -> mongodb::Collection<DailyStats> {
+        self.mongo_db.database.collection::<DailyStats>("daily_stats")
+    }
+
+    fn error_events_collection(&self) -> mongodb::Collection<ErrorGroup> {
+        self.mongo_db.database.collection::<ErrorGroup>("error_events")
+    }
+
+    fn log_performance_collection(&self) -> mongodb::Collection<SystemPerformanceLog> {
+        self.mongo_db.database.collection::<SystemPerformanceLog>("performance_logs")
+    }
+
+}
+
+#[async_trait(?Send)]
+impl LogStore for MongoLogStore {
+    #[instrument(level = "debug", skip_all)]
+    async fn log_request(
+        &self,
+        log: RequestLog,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        collection.insert_one(&log).await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn log_performance(
+        &self,
+        log: SystemPerformanceLog,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let collection = self.log_performance_collection();
+        collection.insert_one(&log).await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_request_stats_by_date(
+        &self,
+        day: NaiveDate,
+    ) -> Result<mongodb::bson::Document, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        
+        let pipeline = vec![
+            day_match_stage(day),
+            doc! {
+                "$group": {
+                    "_id": mongodb::bson::Bson::Null,
+                    "total_requests": { "$sum": 1 },
+                    "avg_response_time": { "$avg": "$response_time_ms" },
+                    "unique_ips": { "$addToSet": "$ip_address" },
+                }
+            },
+        ];
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        
+        if let Some(result) = cursor.try_next().await? {
+            Ok(result)
+        } else {
+            Ok(doc! {})
+        }
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_click_stats(
+        &self,
+        ip: Option<&str>,
+    ) -> Result<Vec<mongodb::bson::Document>, Box<dyn std::error::Error>> {
+        let collection = self.log_click_collection();
+        
+        let filter = match ip {
+            Some(ip_addr) => doc! { "ip_address": ip_addr },
+            None => doc! {},
+        };
+
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! {
+                "$group": {
+                    "_id": "$element",
+                    "click_count": { "$sum": 1 },
+                    "unique_users": { "$addToSet": "$ip_address" }
+                }
+            },
+            doc! { "$sort": { "click_count": -1 } },
+        ];
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        let mut results = Vec::new();
+        
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_requests_by_ip(
+        &self,
+        ip: &str,
+    ) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        let filter = doc! { "ip_address": ip };
+        
+        let mut cursor = collection.find(filter).await?;
+        let mut results = Vec::new();
+        
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_total_requests_by_day(
+        &self,
+    ) -> Result<Vec<mongodb::bson::Document>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        
+        let pipeline = vec![
+            timestamp_date_stage(),
+            doc! {
+                "$group": {
+                    "_id": {
+                        "$dateToString": {
+                            "format": "%Y-%m-%d",
+                            "date": "$timestamp_date"
+                        }
+                    },
+                    "total_requests": { "$sum": 1 },
+                    "avg_response_time": { "$avg": "$response_time_ms" }
+                }
+            },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        let mut results = Vec::new();
+        
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_total_request_count(
+        &self,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        let count = collection.count_documents(doc! {}).await?;
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_all_requests(
+        &self,
+        limit: Option<i64>,
+        skip: Option<u64>,
+    ) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        
+        let mut find_options = mongodb::options::FindOptions::default();
+        find_options.sort = Some(doc! { "timestamp": -1 });
+        if let Some(lim) = limit {
+            find_options.limit = Some(lim);
+        }
+        if let Some(sk) = skip {
+            find_options.skip = Some(sk);
+        }
+        
+        let mut cursor = collection.find(doc! {}).with_options(find_options).await?;
+        let mut results = Vec::new();
+        
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn delete_requests_by_date(
+        &self,
+        day: NaiveDate,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        
+        let result = collection.delete_many(day_document(day)).await?;
+
+        rollup::refresh_days(self, [day]).await?;
+        Ok(result.deleted_count)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn search_requests(
+        &self,
+        filter: &Filter,
+        limit: i64,
+        skip: u64,
+    ) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            doc! { "$match": filter_document(filter) },
+            timestamp_date_stage(),
+            doc! { "$sort": { "timestamp_date": -1 } },
+            doc! { "$skip": skip as i64 },
+            doc! { "$limit": limit },
+            doc! { "$project": { "timestamp_date": 0 } },
+        ];
+
+        let mut cursor = self.log_request_collection().aggregate(pipeline).await?.with_type::<RequestLog>();
+        let mut results = Vec::new();
+        
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn count_matching_requests(
+        &self,
+        filter: &Filter,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            doc! { "$match": filter_document(filter) },
+            doc! { "$count": "count" },
+        ];
+
+        let mut cursor = self.log_request_collection().aggregate(pipeline).await?;
+        let count = cursor.try_next().await?
+            .map(|result| bson_number(result.get("count")) as u64)
+            .unwrap_or(0);
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_requests_by_date(
+        &self,
+        day: NaiveDate,
+    ) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+        
+        let mut find_options = mongodb::options::FindOptions::default();
+        find_options.sort = Some(doc! { "timestamp": -1 });
+        
+        let mut cursor = collection.find(day_document(day)).with_options(find_options).await?;
+        
+        let mut results = Vec::new();
+        
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn compute_daily_stats(
+        &self,
+        day: NaiveDate,
+        exclude_bots: bool,
+    ) -> Result<DailyStats, Box<dyn std::error::Error>> {
+        let request_pipeline = vec![
+            day_match_stage(day),
+            bot_match_stage(exclude_bots),
+            timestamp_date_stage(),
+            doc! {
+                "$facet": {
+                    "totals": [
+                        {
+                            "$group": {
+                                "_id": mongodb::bson::Bson::Null,
+                                "total_requests": { "$sum": 1 },
+                                "avg_response_time": { "$avg": "$response_time_ms" },
+                                "latency": latency_accumulator(),
+                                "unique_ips": { "$addToSet": "$ip_address" }
+                            }
+                        },
+                        { "$project": { "total_requests": 1, "avg_response_time": 1, "latency": 1, "unique_visitors": { "$size": "$unique_ips" } } }
+                    ],
+                    "status_classes": [
+                        { "$group": { "_id": { "$floor": { "$divide": ["$status_code", 100] } }, "count": { "$sum": 1 } } }
+                    ],
+                    "hourly": [
+                        {
+                            "$group": {
+                                "_id": { "$hour": "$timestamp_date" },
+                                "total_requests": { "$sum": 1 },
+                                "unique_ips": { "$addToSet": "$ip_address" }
+                            }
+                        },
+                        { "$project": { "total_requests": 1, "unique_visitors": { "$size": "$unique_ips" } } }
+                    ],
+                    "top_paths": [
+                        { "$group": { "_id": "$path", "count": { "$sum": 1 } } },
+                        { "$sort": { "count": -1 } },
+                        { "$limit": 10 }
+                    ],
+                    "countries": breakdown_facet("country"),
+                    "browsers": breakdown_facet("user_agent_info.browser"),
+                    "operating_systems": breakdown_facet("user_agent_info.os"),
+                    "device_types": breakdown_facet("user_agent_info.device_type"),
+                    "bots": breakdown_facet("user_agent_info.bot_name")
+                }
+            },
+        ];
+
+        let mut cursor = self.log_request_collection().aggregate(request_pipeline).await?;
+        let facets = cursor.try_next().await?.unwrap_or_default();
+
+        let mut stats = DailyStats {
+            date: day.format("%Y-%m-%d").to_string(),
+            excludes_bots: exclude_bots,
+            ..Default::default()
+        };
+
+        let totals = facets.get_array("totals").ok()
+            .and_then(|totals| totals.first())
+            .and_then(|totals| totals.as_document());
+        if let Some(totals) = totals {
+            stats.total_requests = bson_number(totals.get("total_requests")) as u64;
+            stats.unique_visitors = bson_number(totals.get("unique_visitors")) as u64;
+            stats.avg_response_time = bson_number(totals.get("avg_response_time"));
+            stats.latency = parse_latency(totals);
+        }
+
+        if let Ok(classes) = facets.get_array("status_classes") {
+            let mut status_classes = StatusClasses::default();
+            for class in classes.iter().filter_map(|c| c.as_document()) {
+                let count = bson_number(class.get("count")) as u64;
+                match bson_number(class.get("_id")) as u64 {
+                    2 => status_classes.success += count,
+                    3 => status_classes.redirect += count,
+                    4 => status_classes.client_error += count,
+                    5 => status_classes.server_error += count,
+                    _ => {}
+                }
+            }
+            stats.status_classes = status_classes;
+        }
+
+        let mut hourly: Vec<HourlyStats> = (0..24)
+            .map(|hour| HourlyStats { hour, total_requests: 0, unique_visitors: 0, total_clicks: 0 })
+            .collect();
+
+        if let Ok(hours) = facets.get_array("hourly") {
+            for hour in hours.iter().filter_map(|h| h.as_document()) {
+                if let Some(slot) = hourly.get_mut(bson_number(hour.get("_id")) as usize) {
+                    slot.total_requests = bson_number(hour.get("total_requests")) as u64;
+                    slot.unique_visitors = bson_number(hour.get("unique_visitors")) as u64;
+                }
+            }
+        }
+
+        if let Ok(paths) = facets.get_array("top_paths") {
+            stats.top_paths = paths
+                .iter()
+                .filter_map(|p| p.as_document())
+                .map(|p| PathCount {
+                    path: p.get_str("_id").unwrap_or_default().to_string(),
+                    count: bson_number(p.get("count")) as u64,
+                })
+                .collect();
+        }
+
+        stats.countries = parse_breakdown(&facets, "countries");
+        stats.browsers = parse_breakdown(&facets, "browsers");
+        stats.operating_systems = parse_breakdown(&facets, "operating_systems");
+        stats.device_types = parse_breakdown(&facets, "device_types");
+        stats.bots = parse_breakdown(&facets, "bots");
+
+        let click_pipeline = vec![
+            day_match_stage(day),
+            timestamp_date_stage(),
+            doc! {
+                "$group": {
+                    "_id": { "$hour": "$timestamp_date" },
+                    "total_clicks": { "$sum": 1 }
+                }
+            },
+        ];
+
+        let mut cursor = self.log_click_collection().aggregate(click_pipeline).await?;
+        while let Some(hour) = cursor.try_next().await? {
+            let total_clicks = bson_number(hour.get("total_clicks")) as u64;
+            stats.total_clicks += total_clicks;
+            if let Some(slot) = hourly.get_mut(bson_number(hour.get("_id")) as usize) {
+                slot.total_clicks = total_clicks;
+            }
+        }
+
+        stats.hourly = hourly;
+        stats.sessions = SessionStats::from_hits(self.get_page_hits(day, exclude_bots).await?);
+        Ok(stats)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn export_requests(
+        &self,
+        filter: &ExportFilter,
+    ) -> Result<RecordStream<RequestLog>, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            doc! { "$match": filter_document(&filter.request_filter()) },
+            timestamp_date_stage(),
+            doc! { "$sort": { "timestamp_date": 1 } },
+            doc! { "$project": { "timestamp_date": 0 } },
+        ];
+
+        let cursor = self.log_request_collection().aggregate(pipeline).allow_disk_use(true).await?;
+        Ok(cursor.with_type::<RequestLog>().map(|record| record.map_err(Into::into)).boxed_local())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn export_clicks(
+        &self,
+        filter: &ExportFilter,
+    ) -> Result<RecordStream<ClickLog>, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            doc! { "$match": filter_document(&filter.click_filter()) },
+            timestamp_date_stage(),
+            doc! { "$sort": { "timestamp_date": 1 } },
+            doc! { "$project": { "timestamp_date": 0 } },
+        ];
+
+        let cursor = self.log_click_collection().aggregate(pipeline).allow_disk_use(true).await?;
+        Ok(cursor.with_type::<ClickLog>().map(|record| record.map_err(Into::into)).boxed_local())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_page_hits(
+        &self,
+        day: NaiveDate,
+        exclude_bots: bool,
+    ) -> Result<Vec<PageHit>, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            day_match_stage(day),
+            bot_match_stage(exclude_bots),
+            doc! { "$match": { "method": "GET", "status_code": { "$lt": 400 } } },
+            timestamp_date_stage(),
+            doc! {
+                "$project": {
+                    "_id": 0,
+                    "timestamp_date": 1,
+                    "path": 1,
+                    "visitor_id": { "$ifNull": ["$visitor_id", { "$concat": ["$ip_address", "|", "$user_agent"] }] }
+                }
+            },
+        ];
+
+        let mut cursor = self.log_request_collection().aggregate(pipeline).await?;
+        let mut hits = Vec::new();
+
+        while let Some(hit) = cursor.try_next().await? {
+            let Ok(timestamp) = hit.get_datetime("timestamp_date") else { continue };
+            hits.push(PageHit {
+                visitor_id: hit.get_str("visitor_id").unwrap_or_default().to_string(),
+                timestamp_ms: timestamp.timestamp_millis(),
+                path: hit.get_str("path").unwrap_or_default().to_string(),
+            });
+        }
+
+        Ok(hits)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn upsert_daily_stats(
+        &self,
+        stats: &DailyStats,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let collection = self.daily_stats_collection();
+        collection
+            .replace_one(doc! { "date": &stats.date, "excludes_bots": stats.excludes_bots }, stats)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn delete_daily_stats(
+        &self,
+        date: &str,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let result = self.daily_stats_collection().delete_many(doc! { "date": date }).await?;
+        Ok(result.deleted_count)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_daily_stats(
+        &self,
+        exclude_bots: bool,
+    ) -> Result<Vec<DailyStats>, Box<dyn std::error::Error>> {
+        let collection = self.daily_stats_collection();
+
+        let mut find_options = mongodb::options::FindOptions::default();
+        find_options.sort = Some(doc! { "date": 1 });
+
+        let filter = if exclude_bots {
+            doc! { "excludes_bots": true }
+        } else {
+            doc! { "excludes_bots": { "$ne": true } }
+        };
+
+        let mut cursor = collection.find(filter).with_options(find_options).await?;
+        let mut results = Vec::new();
+
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_latest_daily_stats_date(
+        &self,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let collection = self.daily_stats_collection();
+        let latest = collection
+            .find_one(doc! {})
+            .sort(doc! { "date": -1 })
+            .await?;
+        Ok(latest.map(|stats| stats.date))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_earliest_log_date(
+        &self,
+    ) -> Result<Option<NaiveDate>, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            timestamp_date_stage(),
+            doc! {
+                "$group": {
+                    "_id": mongodb::bson::Bson::Null,
+                    "earliest": { "$min": "$timestamp_date" }
+                }
+            },
+        ];
+
+        let mut earliest: Option<NaiveDate> = None;
+
+        let mut cursor = self.log_request_collection().aggregate(pipeline.clone()).await?;
+        if let Some(result) = cursor.try_next().await? {
+            earliest = result.get_datetime("earliest").ok()
+                .and_then(|dt| chrono::DateTime::from_timestamp_millis(dt.timestamp_millis()))
+                .map(|dt| dt.date_naive());
+        }
+
+        let mut cursor = self.log_click_collection().aggregate(pipeline).await?;
+        if let Some(result) = cursor.try_next().await? {
+            let clicks_earliest = result.get_datetime("earliest").ok()
+                .and_then(|dt| chrono::DateTime::from_timestamp_millis(dt.timestamp_millis()))
+                .map(|dt| dt.date_naive());
+            earliest = match (earliest, clicks_earliest) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        Ok(earliest)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_route_performance(
+        &self,
+        since: chrono::DateTime<Utc>,
+        exclude_bots: bool,
+    ) -> Result<Vec<RoutePerformance>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+
+        let pipeline = vec![
+            doc! { "$match": since_document(since) },
+            bot_match_stage(exclude_bots),
+            doc! {
+                "$group": {
+                    "_id": { "$ifNull": ["$route", "$path"] },
+                    "count": { "$sum": 1 },
+                    "avg_response_time": { "$avg": "$response_time_ms" },
+                    "max_response_time": { "$max": "$response_time_ms" },
+                    "latency": latency_accumulator(),
+                }
+            },
+        ];
+
+        // Older rows have no `route`, so their raw paths are normalized and merged here
+        let mut by_route: HashMap<String, RoutePerformance> = HashMap::new();
+        let mut cursor = collection.aggregate(pipeline).await?;
+
+        while let Some(result) = cursor.try_next().await? {
+            let route = normalize_route(result.get_str("_id").unwrap_or_default());
+            let group = RoutePerformance {
+                route: route.clone(),
+                count: bson_number(result.get("count")) as u64,
+                avg_response_time: bson_number(result.get("avg_response_time")),
+                max_response_time: bson_number(result.get("max_response_time")) as u64,
+                latency: parse_latency(&result),
+            };
+            match by_route.entry(route) {
+                Entry::Occupied(mut existing) => existing.get_mut().merge(group),
+                Entry::Vacant(entry) => { entry.insert(group); }
+            }
+        }
+
+        let mut routes: Vec<RoutePerformance> = by_route.into_values().collect();
+        routes.sort_by(|a, b| b.latency.p95.total_cmp(&a.latency.p95));
+
+        Ok(routes)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_traffic_summary(
+        &self,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<TrafficSummary, Box<dyn std::error::Error>> {
+        let pipeline = vec![
+            doc! { "$match": since_document(since) },
+            doc! {
+                "$group": {
+                    "_id": mongodb::bson::Bson::Null,
+                    "requests": { "$sum": 1 },
+                    "server_errors": { "$sum": { "$cond": [{ "$gte": ["$status_code", 500] }, 1, 0] } },
+                    "latency": latency_accumulator(),
+                }
+            },
+        ];
+
+        let mut cursor = self.log_request_collection().aggregate(pipeline).await?;
+        let Some(result) = cursor.try_next().await? else {
+            return Ok(TrafficSummary::default());
+        };
+
+        Ok(TrafficSummary {
+            requests: bson_number(result.get("requests")) as u64,
+            server_errors: bson_number(result.get("server_errors")) as u64,
+            latency: parse_latency(&result),
+        })
+    }
+
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_slowest_requests(
+        &self,
+        since: chrono::DateTime<Utc>,
+        exclude_bots: bool,
+        limit: i64,
+    ) -> Result<Vec<RequestLog>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+
+        let pipeline = vec![
+            doc! { "$match": since_document(since) },
+            bot_match_stage(exclude_bots),
+            doc! { "$sort": { "response_time_ms": -1 } },
+            doc! { "$limit": limit },
+        ];
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        let mut results = Vec::new();
+
+        while let Some(result) = cursor.try_next().await? {
+            results.push(mongodb::bson::from_document::<RequestLog>(result)?);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_performance_logs(
+        &self,
+        since: chrono::DateTime<Utc>,
+    ) -> Result<Vec<SystemPerformanceLog>, Box<dyn std::error::Error>> {
+        let collection = self.log_performance_collection();
+
+        let mut find_options = mongodb::options::FindOptions::default();
+        find_options.sort = Some(doc! { "timestamp": 1 });
+
+        let mut cursor = collection.find(doc! {
+            "timestamp": { "$gte": mongodb::bson::DateTime::from_millis(since.timestamp_millis()) }
+        }).with_options(find_options).await?;
+        let mut results = Vec::new();
+
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_top_referrers(
+        &self,
+        since: chrono::DateTime<Utc>,
+        exclude_bots: bool,
+        limit: i64,
+    ) -> Result<Vec<BreakdownCount>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+
+        let mut pipeline = vec![
+            doc! { "$match": since_document(since) },
+            bot_match_stage(exclude_bots),
+        ];
+        pipeline.extend(breakdown_facet("referrer_host"));
+        pipeline.push(doc! { "$limit": limit });
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        let mut results = Vec::new();
+
+        while let Some(result) = cursor.try_next().await? {
+            results.push(BreakdownCount {
+                name: result.get_str("_id").unwrap_or_default().to_string(),
+                requests: bson_number(result.get("requests")) as u64,
+                unique_visitors: bson_number(result.get("unique_visitors")) as u64,
+            });
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_campaigns(
+        &self,
+        since: chrono::DateTime<Utc>,
+        exclude_bots: bool,
+    ) -> Result<Vec<CampaignCount>, Box<dyn std::error::Error>> {
+        let collection = self.log_request_collection();
+
+        let pipeline = vec![
+            doc! { "$match": since_document(since) },
+            doc! {
+                "$match": {
+                    "$or": [
+                        { "utm_source": { "$type": "string" } },
+                        { "utm_medium": { "$type": "string" } },
+                        { "utm_campaign": { "$type": "string" } },
+                    ]
+                }
+            },
+            bot_match_stage(exclude_bots),
+            doc! {
+                "$group": {
+                    "_id": {
+                        "source": "$utm_source",
+                        "medium": "$utm_medium",
+                        "campaign": "$utm_campaign"
+                    },
+                    "requests": { "$sum": 1 },
+                    "unique_ips": { "$addToSet": "$ip_address" }
+                }
+            },
+            doc! { "$project": { "requests": 1, "unique_visitors": { "$size": "$unique_ips" } } },
+            doc! { "$sort": { "unique_visitors": -1 } },
+        ];
+
+        let mut cursor = collection.aggregate(pipeline).await?;
+        let mut results = Vec::new();
+
+        while let Some(result) = cursor.try_next().await? {
+            let key = result.get_document("_id").cloned().unwrap_or_default();
+            let field = |name: &str| key.get_str(name).ok().map(str::to_string);
+            results.push(CampaignCount {
+                source: field("source"),
+                medium: field("medium"),
+                campaign: field("campaign"),
+                requests: bson_number(result.get("requests")) as u64,
+                unique_visitors: bson_number(result.get("unique_visitors")) as u64,
+            });
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn erase_ip(
+        &self,
+        ip: &str,
+    ) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let filter = doc! { "ip_address": ip };
+
+        // The days to recompute, found before their logs are gone
+        let pipeline = vec![
+            doc! { "$match": filter.clone() },
+            timestamp_date_stage(),
+            doc! { "$group": { "_id": { "$dateToString": { "format": "%Y-%m-%d", "date": "$timestamp_date" } } } },
+        ];
+        let mut days = BTreeSet::new();
+        for mut cursor in [
+            self.log_request_collection().aggregate(pipeline.clone()).await?,
+            self.log_click_collection().aggregate(pipeline).await?,
+        ] {
+            while let Some(day) = cursor.try_next().await? {
+                if let Ok(day) = NaiveDate::parse_from_str(day.get_str("_id").unwrap_or_default(), "%Y-%m-%d") {
+                    days.insert(day);
+                }
+            }
+        }
+
+        let requests = self.log_request_collection().delete_many(filter.clone()).await?;
+        let clicks = self.log_click_collection().delete_many(filter).await?;
+
+        rollup::refresh_days(self, days).await?;
+        Ok((requests.deleted_count, clicks.deleted_count))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn purge_before(
+        &self,
+        before: chrono::DateTime<Utc>,
+    ) -> Result<(u64, u64, u64), Box<dyn std::error::Error>> {
+        let filter = timestamp_document(&(Bound::Unbounded, Bound::Excluded(bson_date(before))));
+
+        let requests = self.log_request_collection().delete_many(filter.clone()).await?;
+        let clicks = self.log_click_collection().delete_many(filter.clone()).await?;
+        let performance = self.log_performance_collection().delete_many(filter).await?;
+
+        Ok((requests.deleted_count, clicks.deleted_count, performance.deleted_count))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn record_error(
+        &self,
+        event: ErrorEvent,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let collection = self.error_events_collection();
+        let fingerprint = event.fingerprint();
+
+        collection
+            .update_one(
+                doc! { "fingerprint": &fingerprint },
+                doc! {
+                    "$setOnInsert": {
+                        "fingerprint": &fingerprint,
+                        "status_code": event.status_code as i32,
+                        "method": &event.method,
+                        "route": &event.route,
+                        "first_seen": event.timestamp,
+                    },
+                    "$set": {
+                        "message": &event.message,
+                        "last_seen": event.timestamp,
+                        "last_path": &event.path,
+                        "last_request_id": &event.request_id,
+                        "details": &event.details,
+                        "resolved": false,
+                        "resolved_at": mongodb::bson::Bson::Null,
+                    },
+                    "$inc": { "count": 1_i64 },
+                    "$push": { "recent_request_ids": { "$each": [&event.request_id], "$slice": -RECENT_REQUEST_IDS } },
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn get_error_groups(
+        &self,
+        include_resolved: bool,
+    ) -> Result<Vec<ErrorGroup>, Box<dyn std::error::Error>> {
+        let collection = self.error_events_collection();
+        let filter = if include_resolved { doc! {} } else { doc! { "resolved": { "$ne": true } } };
+
+        let mut find_options = mongodb::options::FindOptions::default();
+        find_options.sort = Some(doc! { "last_seen": -1 });
+        find_options.limit = Some(200);
+
+        let mut cursor = collection.find(filter).with_options(find_options).await?;
+        let mut results = Vec::new();
+
+        while let Some(result) = cursor.try_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn set_error_resolved(
+        &self,
+        fingerprint: &str,
+        resolved: bool,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let collection = self.error_events_collection();
+        let resolved_at = if resolved {
+            mongodb::bson::Bson::DateTime(mongodb::bson::DateTime::now())
+        } else {
+            mongodb::bson::Bson::Null
+        };
+
+        let result = collection
+            .update_one(
+                doc! { "fingerprint": fingerprint },
+                doc! { "$set": { "resolved": resolved, "resolved_at": resolved_at } },
+            )
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+}
@@ -0,0 +1,134 @@
+// src/logging/user_agent.rs
+use serde::{Deserialize, Serialize};
+use woothee::parser::Parser;
+
+// Substrings that identify automated clients woothee doesn't classify as crawlers
+const BOT_MARKERS: &[&str] = &[
+    "bot", "crawl", "spider", "slurp", "curl", "wget", "python-requests", "python-urllib",
+    "go-http-client", "java/", "okhttp", "httpclient", "scrapy", "headless", "facebookexternalhit",
+    "preview", "monitor", "uptime",
+];
+
+const UNKNOWN: &str = "Unknown";
+
+/// Structured view of a raw `User-Agent` header, parsed once at ingest time
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UserAgentInfo {
+    pub browser: String,
+    pub browser_version: String,
+    pub os: String,
+    pub device_type: String, // desktop, mobile, tablet, appliance, bot, other
+    pub is_bot: bool,
+    pub bot_name: Option<String>,
+}
+
+impl UserAgentInfo {
+    pub fn parse(user_agent: &str) -> Self {
+        let trimmed = user_agent.trim();
+        if trimmed.is_empty() || trimmed == "unknown" || trimmed == "-" {
+            return UserAgentInfo {
+                browser: UNKNOWN.to_string(),
+                browser_version: String::new(),
+                os: UNKNOWN.to_string(),
+                device_type: "bot".to_string(),
+                is_bot: true,
+                bot_name: Some("No user agent".to_string()),
+            };
+        }
+
+        let known = |value: &str| (value != woothee::woothee::VALUE_UNKNOWN).then(|| value.to_string());
+        let parsed = Parser::new().parse(trimmed);
+
+        let (browser, browser_version, os, category) = match &parsed {
+            Some(result) => (
+                known(result.name),
+                known(result.version),
+                known(result.os),
+                result.category,
+            ),
+            None => (None, None, None, woothee::woothee::VALUE_UNKNOWN),
+        };
+
+        let bot_name = if category == "crawler" {
+            browser.clone().or_else(|| find_bot_token(trimmed))
+        } else {
+            find_bot_token(trimmed)
+        };
+        let is_bot = category == "crawler" || bot_name.is_some();
+
+        let lowered = trimmed.to_lowercase();
+        let device_type = if is_bot {
+            "bot"
+        } else if lowered.contains("ipad") || lowered.contains("tablet") {
+            "tablet"
+        } else {
+            match category {
+                "pc" => "desktop",
+                "smartphone" | "mobilephone" => "mobile",
+                "appliance" => "appliance",
+                _ => "other",
+            }
+        };
+
+        UserAgentInfo {
+            browser: browser.unwrap_or_else(|| UNKNOWN.to_string()),
+            browser_version: browser_version.unwrap_or_default(),
+            os: os.unwrap_or_else(|| UNKNOWN.to_string()),
+            device_type: device_type.to_string(),
+            is_bot,
+            bot_name: if is_bot { bot_name.or_else(|| Some(UNKNOWN.to_string())) } else { None },
+        }
+    }
+}
+
+/// Product token containing a bot marker, e.g. `Googlebot/2.1` -> `Googlebot`
+fn find_bot_token(user_agent: &str) -> Option<String> {
+    user_agent
+        .split(|c: char| c.is_whitespace() || c == ';' || c == '(' || c == ')' || c == ',')
+        .find(|token| {
+            let token = token.to_lowercase();
+            BOT_MARKERS.iter().any(|marker| token.contains(marker))
+        })
+        .map(|token| {
+            let token = token.trim_start_matches('+');
+            let name = token.split('/').next().unwrap_or(token);
+            // URLs like "+http://www.google.com/bot.html" only carry the host
+            name.trim_start_matches("http:").trim_start_matches("https:").to_string()
+        })
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_browser() {
+        let info = UserAgentInfo::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        );
+        assert_eq!(info.browser, "Chrome");
+        assert_eq!(info.browser_version, "120.0.0.0");
+        assert_eq!(info.os, "Windows 10");
+        assert_eq!(info.device_type, "desktop");
+        assert!(!info.is_bot);
+        assert_eq!(info.bot_name, None);
+    }
+
+    #[test]
+    fn test_parse_bots() {
+        let google = UserAgentInfo::parse(
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        );
+        assert!(google.is_bot);
+        assert_eq!(google.device_type, "bot");
+        assert_eq!(google.bot_name.as_deref(), Some("Googlebot"));
+
+        let curl = UserAgentInfo::parse("curl/8.4.0");
+        assert!(curl.is_bot);
+        assert_eq!(curl.bot_name.as_deref(), Some("curl"));
+
+        let missing = UserAgentInfo::parse("unknown");
+        assert!(missing.is_bot);
+    }
+}
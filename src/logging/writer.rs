@@ -0,0 +1,245 @@
+// src/logging/writer.rs
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use actix_web::rt::task::JoinHandle;
+use futures::StreamExt;
+use futures::channel::mpsc;
+use tracing::{error, warn, Instrument, Span};
+use crate::logging::{geoip, live, metrics, LogStore};
+use crate::logging::error_tracking::ErrorEvent;
+use crate::logging::request_logger::RequestLog;
+use crate::logging::user_agent::UserAgentInfo;
+
+/// Writes in progress at once, so one slow insert doesn't hold up the rest of the queue
+const MAX_CONCURRENT_WRITES: usize = 32;
+
+/// Queued writes at which the writer counts as not keeping up with traffic, and new writes are
+/// dropped rather than queued without limit
+const MAX_HEALTHY_BACKLOG: u64 = 10_000;
+
+/// Failed writes in a row after which the store is assumed to be unreachable
+const MAX_CONSECUTIVE_FAILURES: u64 = 5;
+
+pub enum LogWrite {
+    /// Enriched with user-agent and GeoIP data (from the real `client_ip`) before it is stored
+    Request { log: Box<RequestLog>, client_ip: String },
+    Error(ErrorEvent),
+}
+
+struct Queued {
+    write: LogWrite,
+    /// The request span, so the write's own events stay correlated with the request
+    span: Span,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    queued: AtomicU64,
+    finished: AtomicU64,
+    /// Reset by every successful write
+    consecutive_failures: AtomicU64,
+}
+
+impl Counters {
+    fn pending(&self) -> u64 {
+        self.queued.load(Ordering::Relaxed).saturating_sub(self.finished.load(Ordering::Relaxed))
+    }
+}
+
+/// Queues log writes for a task on the main arbiter, so they aren't lost with the worker that
+/// handled the request and can be flushed on shutdown. Cloned into each worker's app data.
+#[derive(Clone)]
+pub struct LogWriter {
+    tx: mpsc::UnboundedSender<Queued>,
+    counters: Arc<Counters>,
+}
+
+/// The task behind a `LogWriter`, kept by `main` to flush it on shutdown
+pub struct LogWriterTask {
+    handle: JoinHandle<()>,
+    tx: mpsc::UnboundedSender<Queued>,
+    counters: Arc<Counters>,
+}
+
+/// Outcome of `LogWriterTask::flush`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlushReport {
+    /// Writes still queued at shutdown that made it to the store (or failed there)
+    pub flushed: u64,
+    /// Writes abandoned when the timeout ran out
+    pub dropped: u64,
+}
+
+impl LogWriter {
+    /// Start the writer task on the current arbiter
+    pub fn spawn(store: Arc<dyn LogStore>) -> (LogWriter, LogWriterTask) {
+        let (tx, rx) = mpsc::unbounded::<Queued>();
+        let counters = Arc::new(Counters::default());
+
+        let task_counters = Arc::clone(&counters);
+        let handle = actix_web::rt::spawn(rx.for_each_concurrent(MAX_CONCURRENT_WRITES, move |queued| {
+            let store = Arc::clone(&store);
+            let counters = Arc::clone(&task_counters);
+            async move {
+                if write(&*store, queued.write).await {
+                    counters.consecutive_failures.store(0, Ordering::Relaxed);
+                } else {
+                    counters.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+                }
+                counters.finished.fetch_add(1, Ordering::Relaxed);
+                metrics::LOG_WRITES_PENDING.dec();
+            }
+            .instrument(queued.span)
+        }));
+
+        let task = LogWriterTask { handle, tx: tx.clone(), counters: Arc::clone(&counters) };
+        (LogWriter { tx, counters }, task)
+    }
+
+    /// Queue a write without waiting for it, or drop it when the backlog is full
+    pub fn submit(&self, write: LogWrite) {
+        if self.counters.pending() >= MAX_HEALTHY_BACKLOG {
+            metrics::LOG_WRITES_DROPPED_TOTAL.inc();
+            return;
+        }
+
+        let queued = Queued { write, span: Span::current() };
+        match self.tx.unbounded_send(queued) {
+            Ok(()) => {
+                self.counters.queued.fetch_add(1, Ordering::Relaxed);
+                metrics::LOG_WRITES_PENDING.inc();
+            }
+            Err(_) => warn!("Log writer is shut down, dropping log write"),
+        }
+    }
+
+    /// The number of queued writes, or why the writer isn't keeping up
+    pub fn check(&self) -> Result<u64, String> {
+        let pending = self.counters.pending();
+        let failures = self.counters.consecutive_failures.load(Ordering::Relaxed);
+
+        if self.tx.is_closed() {
+            Err("writer is shut down".to_string())
+        } else if failures >= MAX_CONSECUTIVE_FAILURES {
+            Err(format!("last {} writes failed", failures))
+        } else if pending >= MAX_HEALTHY_BACKLOG {
+            Err(format!("{} writes queued, dropping new ones", pending))
+        } else {
+            Ok(pending)
+        }
+    }
+}
+
+impl LogWriterTask {
+    /// Stop accepting writes and wait up to `timeout` for the queued ones to finish
+    pub async fn flush(mut self, timeout: Duration) -> FlushReport {
+        self.tx.close_channel();
+        let pending = self.counters.pending();
+
+        if actix_web::rt::time::timeout(timeout, &mut self.handle).await.is_err() {
+            self.handle.abort();
+        }
+
+        let dropped = self.counters.pending();
+        FlushReport { flushed: pending.saturating_sub(dropped), dropped }
+    }
+}
+
+/// Returns whether the store accepted the write
+async fn write(store: &dyn LogStore, write: LogWrite) -> bool {
+    match write {
+        LogWrite::Request { mut log, client_ip } => {
+            // Enrichment happens here, off the response path
+            log.user_agent_info = Some(UserAgentInfo::parse(&log.user_agent));
+            if let Some(geo) = geoip::lookup(&client_ip) {
+                log.location = geo.location;
+                log.country = geo.country;
+                log.city = geo.city;
+            }
+
+            let ua_info = log.user_agent_info.as_ref();
+            live::publish(
+                log.visitor_id.as_deref().unwrap_or(&log.ip_address),
+                live::LiveRequest {
+                    timestamp: log.timestamp.timestamp_millis(),
+                    method: log.method.clone(),
+                    path: log.path.clone(),
+                    status_code: log.status_code,
+                    response_time_ms: log.response_time_ms,
+                    country: log.country.clone(),
+                    browser: ua_info.map(|info| info.browser.clone()),
+                    is_bot: ua_info.is_some_and(|info| info.is_bot),
+                },
+            );
+
+            if let Err(e) = store.log_request(*log).await {
+                metrics::LOG_WRITE_FAILURES_TOTAL.inc();
+                error!(error = %e, "Failed to log request");
+                return false;
+            }
+            true
+        }
+        LogWrite::Error(event) => {
+            if let Err(e) = store.record_error(event).await {
+                metrics::LOG_WRITE_FAILURES_TOTAL.inc();
+                error!(error = %e, "Failed to record error event");
+                return false;
+            }
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::store::MemoryLogStore;
+
+    fn error_event() -> ErrorEvent {
+        ErrorEvent {
+            timestamp: mongodb::bson::DateTime::now(),
+            request_id: "test".to_string(),
+            method: "GET".to_string(),
+            path: "/missing".to_string(),
+            route: "unmatched".to_string(),
+            status_code: 404,
+            message: "Not Found".to_string(),
+            details: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_flush_writes_queued_events() {
+        let store: Arc<dyn LogStore> = Arc::new(MemoryLogStore::new());
+        let (writer, task) = LogWriter::spawn(Arc::clone(&store));
+        for _ in 0..3 {
+            writer.submit(LogWrite::Error(error_event()));
+        }
+
+        let report = task.flush(Duration::from_secs(5)).await;
+        assert_eq!(report, FlushReport { flushed: 3, dropped: 0 });
+        assert_eq!(writer.check(), Err("writer is shut down".to_string()));
+        assert_eq!(store.get_error_groups(false).await.unwrap()[0].count, 3);
+
+        // Writes after the flush are refused rather than lost silently
+        writer.submit(LogWrite::Error(error_event()));
+        assert_eq!(writer.counters.queued.load(Ordering::Relaxed), 3);
+    }
+
+    #[actix_web::test]
+    async fn test_submit_drops_writes_over_backlog() {
+        let store: Arc<dyn LogStore> = Arc::new(MemoryLogStore::new());
+        let (writer, task) = LogWriter::spawn(Arc::clone(&store));
+
+        // The writer task doesn't run until this test yields, so everything stays queued
+        for _ in 0..MAX_HEALTHY_BACKLOG + 5 {
+            writer.submit(LogWrite::Error(error_event()));
+        }
+        assert_eq!(writer.counters.queued.load(Ordering::Relaxed), MAX_HEALTHY_BACKLOG);
+        assert!(writer.check().is_err());
+
+        let report = task.flush(Duration::from_secs(30)).await;
+        assert_eq!(report, FlushReport { flushed: MAX_HEALTHY_BACKLOG, dropped: 0 });
+    }
+}
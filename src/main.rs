@@ -4,7 +4,8 @@ use actix_web::{middleware, web, App, HttpMessage, HttpServer};
 use actix_web::{middleware::ErrorHandlers, http::StatusCode};
 use actix_web::middleware::NormalizePath;
 use dotenv::dotenv;
-use std::env;
+use clap::Parser;
+use std::time::Duration;
 
 mod middlewares;
 use middlewares::errors;
@@ -13,16 +14,19 @@ use middlewares::request_logging::RequestLogging;
 use crate::db::connect_with_retry;
 
 mod config;
-use config::{TEMPLATES, IS_DEV};
+use config::{app_config, TEMPLATES, IS_DEV, ROLLUP_INTERVAL_SECS, PERFORMANCE_SAMPLE_SECS, METRICS_PORT, ALERTS, LOG_FORMAT, OTLP, LOG_STORE};
 
 mod routes;
 mod db;
 mod types;
 mod logging;
+mod alerts;
+mod cli;
 
-use routes::{pages_scope, api_scope, logs_scope};
-use logging::{LoggerDb, RequestLogger};
-use actix_web::middleware::Logger;
+use routes::{pages_scope, api_scope, logs_scope, metrics_handler};
+use logging::RequestLogger;
+use logging::performance::ConnectionGuard;
+use tracing::{error, info, warn};
 
 // -------------------- Server bootstrap --------------------
 
@@ -30,59 +34,176 @@ use actix_web::middleware::Logger;
 async fn main() -> std::io::Result<()> {
     // Load env
     dotenv().ok();
+
+    // Load and validate config before anything reads it
+    let cli = cli::Cli::parse();
+    let loaded = match config::AppConfig::load(cli.config.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if cli.print_config {
+        print!("{}", loaded);
+        return Ok(());
+    }
+    config::init(loaded);
     
+    let command = cli.command.unwrap_or(cli::Command::Serve);
+    let serving = matches!(command, cli::Command::Serve);
+
     // Setup logging
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let tracer_provider = match logging::telemetry::init(*LOG_FORMAT, OTLP.as_ref(), !serving) {
+        Ok(provider) => provider,
+        Err(problem) => {
+            eprintln!("{}", config::ConfigError { problems: vec![problem] });
+            std::process::exit(1);
+        }
+    };
     
-    // Parse config
-    let port: u16 = env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse()
-        .expect("PORT must be a valid number");
-    let host = env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let result = if serving {
+        serve().await
+    } else {
+        cli::run(command).await.map_err(|e| std::io::Error::other(e.to_string()))
+    };
+
+    // Flush spans still waiting in the batch exporter
+    if let Some(provider) = tracer_provider
+        && let Err(e) = provider.shutdown()
+    {
+        error!(error = %e, "Failed to flush OTLP spans");
+    }
+
+    if let Err(e) = &result
+        && !serving
+    {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+    result
+}
+
+async fn serve() -> std::io::Result<()> {
+    let host = app_config().server.host.clone();
+    let port = app_config().server.port;
     let env_mode = if *IS_DEV { "development" } else { "production" };
     
-    println!("🚀 Starting server at http://{}:{}", host, port);
-    println!("🔧 Environment: {}", env_mode);
-    println!("📁 Serving templates from ./templates");
-    println!("📦 Serving static files from ./static");
+    info!(%host, port, environment = env_mode, "Starting server");
+    
+    if let Some(otlp) = OTLP.as_ref() {
+        info!(service_name = %otlp.service_name, "Exporting traces over OTLP");
+    }
     
     if *IS_DEV {
-        println!("⚠️  Development mode: Detailed errors will be shown");
-        println!("💡 Set RUST_ENV=production to hide error details");
+        warn!("Development mode: detailed errors will be shown, set RUST_ENV=production to hide them");
     }
     
+    // Load the optional GeoIP database up front rather than on the first logged request
+    lazy_static::initialize(&logging::geoip::GEOIP);
+
     // Connect to MongoDB with retry logic
-    println!("🔌 Connecting to MongoDB...");
-    let mongodb = match connect_with_retry().await {
+    info!("Connecting to MongoDB");
+    let mongodb = match connect_with_retry(&app_config().mongodb).await {
         Ok(m) => m,
         Err(e) => {
-            eprintln!("❌ Failed to connect to MongoDB after retries: {}", e);
+            error!(error = %e, "Failed to connect to MongoDB after retries");
             std::process::exit(1);
         }
     };
-    println!("✅ MongoDB connected successfully!");
+    info!("MongoDB connected");
+
+    // The rollups and alert rules aggregate with `$percentile`, which older servers reject
+    if *LOG_STORE == logging::store::LogStoreKind::Mongo
+        && let Err(e) = db::check_server_version(&mongodb).await
+    {
+        error!(error = %e, "Unsupported MongoDB version");
+        std::process::exit(1);
+    }
+
+    if app_config().mongodb.migrate_on_startup {
+        match db::migrations::run_pending(&mongodb.database).await {
+            Ok(applied) if applied.is_empty() => {}
+            Ok(applied) => info!(?applied, "Applied database migrations"),
+            Err(e) => {
+                error!(error = %e, "Database migration failed");
+                std::process::exit(1);
+            }
+        }
+    }
     
     use std::sync::Arc;
     let mongodb = Arc::new(mongodb);
+
+    // One store shared by the app, the background jobs and the workers
+    let log_store = match logging::store::open_log_store(&LOG_STORE, Some(&mongodb)) {
+        Ok(store) => store,
+        Err(e) => {
+            error!(error = %e, "Failed to open the log store");
+            std::process::exit(1);
+        }
+    };
+    info!(store = ?*LOG_STORE, "Log store ready");
+
+    // Request and error logs are written by one task that outlives the workers, so shutdown can flush it
+    let (log_writer, log_writer_task) = logging::writer::LogWriter::spawn(Arc::clone(&log_store));
+
+    let post_repository: Arc<dyn db::PostRepository> = Arc::new(db::repository::MongoPostRepository::new(&mongodb.database));
+    let user_repository: Arc<dyn db::UserRepository> = Arc::new(db::repository::MongoUserRepository::new(&mongodb.database));
+
+    // Keep daily rollups fresh so the dashboard doesn't aggregate the full history
+    logging::rollup::spawn_rollup_job(
+        Arc::clone(&log_store),
+        std::time::Duration::from_secs(*ROLLUP_INTERVAL_SECS),
+    );
+
+    // Periodically record process memory/CPU, connections and in-flight requests
+    logging::performance::spawn_performance_sampler(
+        Arc::clone(&log_store),
+        std::time::Duration::from_secs(*PERFORMANCE_SAMPLE_SECS),
+    );
     
-    HttpServer::new(move || {
+    // Evaluate alert rules when a webhook or SMTP server is configured to receive them
+    if ALERTS.is_enabled() {
+        info!(interval_secs = ALERTS.interval.as_secs(), "Alerts enabled");
+        alerts::spawn_alert_job(ALERTS.clone(), Arc::clone(&log_store), Arc::clone(&mongodb));
+    }
+
+    // Serve /metrics on a separate admin port when configured, otherwise on the main app
+    if let Some(metrics_port) = *METRICS_PORT {
+        info!(%host, port = metrics_port, "Serving metrics on the admin port");
+        let metrics_server = HttpServer::new(|| App::new().service(metrics_handler))
+            .workers(1)
+            .bind((host.as_str(), metrics_port))?
+            .run();
+        actix_web::rt::spawn(metrics_server);
+    }
+
+    let shutdown_mongodb = Arc::clone(&mongodb);
+    let result = HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(LoggerDb::new(&mongodb)))
+            .app_data(web::Data::new(log_writer.clone()))
+            .app_data(web::Data::from(log_store.clone()))
             .app_data(web::Data::from(mongodb.clone()))
+            .app_data(web::Data::from(post_repository.clone()))
+            .app_data(web::Data::from(user_repository.clone()))
 
             // Routes - organized by scope
             .service(api_scope())
             .service(logs_scope())
+            .configure(|cfg| {
+                if METRICS_PORT.is_none() {
+                    cfg.service(metrics_handler);
+                }
+            })
             .service(pages_scope())
 
             // Static files (CSS, JS, images, etc.)
             .service(fs::Files::new("/static", "./static").show_files_listing())
             
             // Middleware (order matters - applied in reverse order)
-            .wrap(RequestLogging) // Our custom request logging
+            .wrap(RequestLogging) // Request span, access log and analytics logging
             .wrap(NormalizePath::trim())
-            .wrap(Logger::default())
             .wrap(middleware::Compress::default())
             .wrap(
                 ErrorHandlers::new()
@@ -90,7 +211,31 @@ async fn main() -> std::io::Result<()> {
                     .handler(StatusCode::NOT_FOUND, not_found_handler)
             )
     })
+    // The guard lives in the connection's extensions and is dropped when it closes
+    .on_connect(|_conn, ext| {
+        ext.insert(ConnectionGuard::new());
+    })
+    // On SIGINT/SIGTERM, stop accepting connections and give in-flight requests this long to finish
+    .shutdown_timeout(app_config().server.shutdown_timeout_secs)
     .bind((host.as_str(), port))?
     .run()
-    .await
+    .await;
+
+    info!("Server stopped, flushing log writes");
+    let report = log_writer_task
+        .flush(Duration::from_secs(app_config().logging.flush_timeout_secs))
+        .await;
+    if report.dropped > 0 {
+        warn!(flushed = report.flushed, dropped = report.dropped, "Timed out flushing log writes");
+    } else {
+        info!(flushed = report.flushed, "Log writes flushed");
+    }
+
+    // Waits for open cursors, which the background jobs may hold mid-run
+    match actix_web::rt::time::timeout(Duration::from_secs(5), shutdown_mongodb.client.clone().shutdown()).await {
+        Ok(()) => info!("MongoDB connection closed"),
+        Err(_) => warn!("Timed out closing the MongoDB connection"),
+    }
+
+    result
 }
\ No newline at end of file
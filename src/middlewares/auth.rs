@@ -0,0 +1,41 @@
+// src/middlewares/auth.rs
+use std::future::{ready, Ready};
+use actix_web::dev::Payload;
+use actix_web::error::InternalError;
+use actix_web::{FromRequest, HttpRequest, HttpResponse};
+use crate::config::ADMIN_TOKEN;
+
+/// Whether the request carries `Authorization: Bearer <token>`
+pub fn has_bearer_token(req: &HttpRequest, token: &str) -> bool {
+    req.headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|provided| provided == token)
+}
+
+/// Extractor for destructive or outbound admin endpoints, which require `ADMIN_TOKEN` as a bearer
+/// token. Without a configured token they are disabled.
+pub struct AdminAuth;
+
+impl FromRequest for AdminAuth {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let response = match ADMIN_TOKEN.as_deref() {
+            Some(token) if has_bearer_token(req, token) => return ready(Ok(AdminAuth)),
+            Some(_) => HttpResponse::Unauthorized()
+                .insert_header(("WWW-Authenticate", "Bearer"))
+                .json(serde_json::json!({
+                    "success": false,
+                    "message": "This endpoint requires the admin token as a bearer token"
+                })),
+            None => HttpResponse::Forbidden().json(serde_json::json!({
+                "success": false,
+                "message": "Admin endpoints are disabled, set ADMIN_TOKEN to enable them"
+            })),
+        };
+        ready(Err(InternalError::from_response("admin authentication failed", response).into()))
+    }
+}
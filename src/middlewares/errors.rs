@@ -1,7 +1,42 @@
 // src/errors.rs
-use actix_web::{dev::ServiceResponse, http::StatusCode, middleware::ErrorHandlerResponse, HttpResponse, Result, error};
+use std::backtrace::Backtrace;
+use std::fmt;
+use actix_web::{dev::ServiceResponse, http::StatusCode, middleware::ErrorHandlerResponse, HttpResponse, ResponseError, Result, error};
 use tera::Context;
+use tracing::error;
 use crate::{TEMPLATES, IS_DEV};
+use crate::logging::request_logger::REQUEST_ID_HEADER;
+use crate::logging::RequestLogger;
+
+/// A server error raised by a handler. In dev it keeps a backtrace of where it was raised, which
+/// error tracking stores with the error group.
+#[derive(Debug)]
+pub struct AppError {
+    message: String,
+    backtrace: Option<Backtrace>,
+}
+
+impl AppError {
+    pub fn new(message: impl fmt::Display) -> Self {
+        AppError {
+            message: message.to_string(),
+            backtrace: IS_DEV.then(Backtrace::force_capture),
+        }
+    }
+
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Rendered as a 500, which `internal_server_error_handler` turns into the error page
+impl ResponseError for AppError {}
 
 // ---------- Error Handlers ----------
 
@@ -19,9 +54,11 @@ where
         status.canonical_reason().unwrap_or("Unknown error").to_string()
     };
     
-    eprintln!("Internal server error: {}", error_message);
-
     let (req, _res) = res.into_parts();
+    // Shown on error pages so users can quote it when reporting a problem
+    let request_id = RequestLogger::request_id(&req).unwrap_or_default();
+
+    error!(%request_id, error = %error_message, "Internal server error");
 
     // Detect if JSON should be returned instead of HTML
     let is_json_request = 
@@ -31,9 +68,12 @@ where
         });
 
     if is_json_request {
-        let json_response = HttpResponse::InternalServerError().json(serde_json::json!({
+        let json_response = HttpResponse::InternalServerError()
+            .insert_header((REQUEST_ID_HEADER, request_id.as_str()))
+            .json(serde_json::json!({
             "status": "error",
             "message": status.canonical_reason().unwrap_or("Internal Server Error"),
+            "request_id": request_id,
             "details": if *IS_DEV { Some(error_message.as_str()) } else { None }
         }));
 
@@ -46,6 +86,7 @@ where
     let body = if *IS_DEV {
         let mut ctx = Context::new();
         ctx.insert("error_details", &error_message);
+        ctx.insert("request_id", &request_id);
 
         match TEMPLATES.render("errors/500-dev.html", &ctx) {
             Ok(html) => html,
@@ -75,6 +116,7 @@ where
         <strong>Error Details:</strong>
         <pre>{}</pre>
     </div>
+    <p>Request ID: <code>{}</code></p>
     
     <div class="tip">
         <strong>💡 Debugging Tips:</strong>
@@ -87,12 +129,16 @@ where
     </div>
 </body>
 </html>"#,
-                    html_escape::encode_text(&error_message)
+                    html_escape::encode_text(&error_message),
+                    html_escape::encode_text(&request_id)
                 )
             }
         }
     } else {
-        match TEMPLATES.render("errors/500.html", &Context::new()) {
+        let mut ctx = Context::new();
+        ctx.insert("request_id", &request_id);
+
+        match TEMPLATES.render("errors/500.html", &ctx) {
             Ok(html) => html,
             Err(_) => {
                 r#"<!DOCTYPE html>
@@ -112,14 +158,16 @@ where
     <h1>500</h1>
     <h2>Internal Server Error</h2>
     <p>Something went wrong on our end. Please try again later.</p>
+    <p>Request ID: <code>{}</code></p>
     <p><a href="/">← Back to Home</a></p>
 </body>
-</html>"#.to_string()
+</html>"#.replace("{}", &html_escape::encode_text(&request_id))
             }
         }
     };
 
     let new_response = HttpResponse::InternalServerError()
+        .insert_header((REQUEST_ID_HEADER, request_id.as_str()))
         .content_type("text/html; charset=utf-8")
         .body(body);
 
@@ -135,6 +183,7 @@ where
 {
     let (req, _res) = res.into_parts();
     let path = req.path().to_string();
+    let request_id = RequestLogger::request_id(&req).unwrap_or_default();
 
     // Detect if JSON should be returned instead of HTML
     let is_json_request = 
@@ -144,9 +193,12 @@ where
         });
 
     if is_json_request {
-        let json_response = HttpResponse::NotFound().json(serde_json::json!({
+        let json_response = HttpResponse::NotFound()
+            .insert_header((REQUEST_ID_HEADER, request_id.as_str()))
+            .json(serde_json::json!({
             "status": "error",
             "message": "Not Found",
+            "request_id": request_id,
             "details": if *IS_DEV { Some(path.as_str()) } else { None }
         }));
 
@@ -178,13 +230,18 @@ where
     <h2>Page Not Found</h2>
     <p>The requested path was not found:</p>
     <div class="path">{}</div>
+    <p>Request ID: <code>{}</code></p>
     <p><a href="/">← Back to Home</a></p>
 </body>
 </html>"#,
-            html_escape::encode_text(&path)
+            html_escape::encode_text(&path),
+            html_escape::encode_text(&request_id)
         )
     } else {
-        match TEMPLATES.render("errors/404.html", &Context::new()) {
+        let mut ctx = Context::new();
+        ctx.insert("request_id", &request_id);
+
+        match TEMPLATES.render("errors/404.html", &ctx) {
             Ok(html) => html,
             Err(_) => {
                 r#"<!DOCTYPE html>
@@ -193,14 +250,16 @@ where
 <body>
     <h1>404 Not Found</h1>
     <p>The page you're looking for doesn't exist.</p>
+    <p>Request ID: <code>{}</code></p>
     <p><a href="/">Go Home</a></p>
 </body>
-</html>"#.to_string()
+</html>"#.replace("{}", &html_escape::encode_text(&request_id))
             }
         }
     };
 
     let new_response = HttpResponse::NotFound()
+        .insert_header((REQUEST_ID_HEADER, request_id.as_str()))
         .content_type("text/html; charset=utf-8")
         .body(body);
 
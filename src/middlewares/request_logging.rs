@@ -5,8 +5,17 @@ use actix_web::{
 use futures::future::LocalBoxFuture;
 use std::future::{ready, Ready};
 use std::time::Instant;
+use tracing::{field, info, info_span, Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-use crate::logging::{LoggerDb, RequestLogger, request_logger::RequestLog};
+use crate::logging::{performance, RequestLogger, request_logger::RequestLog};
+use crate::logging::{metrics, privacy, telemetry};
+use crate::logging::writer::{LogWrite, LogWriter};
+use crate::logging::performance::InFlightGuard;
+use crate::logging::error_tracking::ErrorEvent;
+use crate::middlewares::errors::AppError;
+use crate::logging::request_logger::{normalize_referrer_host, RequestId, REQUEST_ID_HEADER};
+use crate::config::{HONOR_DO_NOT_TRACK, IS_DEV, STORE_FULL_REFERRER};
 
 pub struct RequestLogging;
 
@@ -45,58 +54,131 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let start = Instant::now();
-        let request_id = RequestLogger::create_request_id();
+        let in_flight = InFlightGuard::new();
+        let request_id = RequestLogger::resolve_request_id(req.request());
         
         // Extract request info before moving req
-        let ip = RequestLogger::extract_ip(req.request());
+        // Anonymized after the GeoIP lookup, which needs the real address
+        let client_ip = RequestLogger::extract_client_ip(req.request());
         let user_agent = RequestLogger::extract_user_agent(req.request());
         let method = RequestLogger::extract_method(req.request());
         let path = RequestLogger::extract_path(req.request());
+        let referrer = RequestLogger::extract_referrer(req.request());
+        let utm = RequestLogger::extract_utm_params(req.request());
         
-        // Skip logging for static files and log endpoints themselves
-        let should_log = !path.starts_with("/static") && !path.starts_with("/api/log");
+        // Skip logging for static files, log endpoints themselves and clients opting out of tracking
+        let opted_out = *HONOR_DO_NOT_TRACK && privacy::has_opted_out(req.request());
+        let should_log = !path.starts_with("/static") && !path.starts_with("/api/log") && !opted_out;
         
-        // Get logger_db reference
-        let logger_db = req.app_data::<web::Data<LoggerDb>>().cloned();
+        // Writes are queued so the response doesn't wait on the store
+        let log_writer = req.app_data::<web::Data<LogWriter>>().cloned();
         
-        req.extensions_mut().insert(request_id.clone());
+        req.extensions_mut().insert(RequestId(request_id.clone()));
+
+        // Everything logged while handling the request, including the spawned logging tasks, carries these fields
+        let span = info_span!(
+            "request",
+            request_id = %request_id,
+            method = %method,
+            path = %path,
+            route = field::Empty,
+            status = field::Empty,
+            latency_ms = field::Empty,
+            otel.name = %method,
+            otel.kind = "server",
+            otel.status_code = field::Empty,
+        );
+        // Continue the caller's trace when it sent a `traceparent`; a no-op while OTLP export is off
+        let _ = span.set_parent(telemetry::remote_context(req.headers()));
         
-        let fut = self.service.call(req);
+        let fut = span.in_scope(|| self.service.call(req));
 
         Box::pin(async move {
-            let res = fut.await?;
+            // Counted as in flight until the inner service has produced a response
+            let _in_flight = in_flight;
+            let mut res = fut.await?;
+            // Echoed so clients and upstream proxies can quote it when reporting a problem
+            if let Ok(value) = actix_web::http::header::HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(actix_web::http::header::HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            performance::record_response(start.elapsed().as_millis() as u64);
+
+            let span = Span::current();
+            let route = res.request().match_pattern();
+            span.record("route", route.as_deref().unwrap_or("unmatched"));
+            span.record("otel.name", format!("{} {}", method, route.as_deref().unwrap_or("unmatched")));
+            if res.status().is_server_error() {
+                span.record("otel.status_code", "ERROR");
+            }
+            span.record("status", res.status().as_u16());
+            span.record("latency_ms", start.elapsed().as_millis() as u64);
+            info!(client_ip = %privacy::anonymize_ip(&client_ip), "request completed");
+
+            // Unmatched paths share one label so scanners can't blow up metric cardinality
+            metrics::observe_request(
+                res.request().match_pattern().as_deref().unwrap_or("unmatched"),
+                &method,
+                res.status().as_u16(),
+                start.elapsed().as_secs_f64(),
+            );
+
+            // Failed requests are tracked even when the request itself isn't logged (e.g. Do-Not-Track),
+            // since the event holds no visitor data. Missing static files are just noise.
+            let status = res.status();
+            let track_error = status.is_server_error() || (status.is_client_error() && !path.starts_with("/static"));
+            if let (true, Some(log_writer)) = (track_error, &log_writer) {
+                let error = res.response().error();
+                let event = ErrorEvent {
+                    timestamp: mongodb::bson::DateTime::now(),
+                    request_id: request_id.clone(),
+                    method: method.clone(),
+                    path: path.clone(),
+                    route: res.request().match_pattern().unwrap_or_else(|| "unmatched".to_string()),
+                    status_code: status.as_u16(),
+                    message: error
+                        .map(|e| e.to_string())
+                        .unwrap_or_else(|| status.canonical_reason().unwrap_or("Unknown error").to_string()),
+                    details: error.filter(|_| *IS_DEV).map(|e| match e.as_error::<AppError>().and_then(AppError::backtrace) {
+                        Some(backtrace) => format!("{}\n\n{}", e, backtrace),
+                        None => format!("{:?}", e),
+                    }),
+                };
+                log_writer.submit(LogWrite::Error(event));
+            }
             
             if should_log {
+                let route = RequestLogger::extract_route(res.request());
                 let elapsed = start.elapsed().as_millis() as u64;
                 let status_code = res.status().as_u16();
                 
-                if let Some(logger_db) = logger_db {
+                if let Some(log_writer) = log_writer {
                     let request_log = RequestLog {
                         id: None,
                         timestamp: mongodb::bson::DateTime::now(),
                         request_id,
-                        ip_address: ip,
+                        ip_address: privacy::anonymize_ip(&client_ip),
+                        visitor_id: Some(privacy::visitor_id(&client_ip, &user_agent)),
                         user_agent,
+                        user_agent_info: None,
                         method,
                         path,
+                        route: Some(route),
                         status_code,
                         response_time_ms: elapsed,
                         location: None,
                         country: None,
                         city: None,
+                        referrer_host: referrer.as_deref().and_then(normalize_referrer_host),
+                        referrer: referrer.filter(|_| *STORE_FULL_REFERRER),
+                        utm_source: utm.source,
+                        utm_medium: utm.medium,
+                        utm_campaign: utm.campaign,
                     };
-                    
-                    // Log asynchronously without blocking the response
-                    let logger_db_clone = logger_db.clone();
-                    actix_web::rt::spawn(async move {
-                        if let Err(e) = logger_db_clone.log_request(request_log).await {
-                            eprintln!("Failed to log request: {}", e);
-                        }
-                    });
+                    log_writer.submit(LogWrite::Request { log: Box::new(request_log), client_ip });
                 }
             }
             
             Ok(res)
-        })
+        }.instrument(span))
     }
 }
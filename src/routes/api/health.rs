@@ -1,25 +1,119 @@
-use actix_web::{get, web, Responder, HttpResponse};
+use std::time::{Duration, Instant};
+use actix_web::{get, routes, web, HttpRequest, HttpResponse, Responder};
+use serde::Serialize;
+use crate::config::TEMPLATES;
 use crate::db::MongoDb;
+use crate::logging::writer::LogWriter;
+use tracing::warn;
 
+/// How long the readiness probe waits on the MongoDB ping
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Templates every page depends on, including the error pages
+const REQUIRED_TEMPLATES: [&str; 3] = ["index.html", "errors/404.html", "errors/500.html"];
+
+#[derive(Serialize)]
+struct ComponentHealth {
+    status: &'static str,
+    latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ComponentHealth {
+    fn from_result(result: Result<(), String>, started: Instant) -> Self {
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+        match result {
+            Ok(()) => ComponentHealth { status: "up", latency_ms, error: None },
+            Err(error) => ComponentHealth { status: "down", latency_ms, error: Some(error) },
+        }
+    }
+
+    fn is_up(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Liveness: the process is up and serving requests, whatever the state of its dependencies
+#[get("/health/live")]
+pub async fn live() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "alive",
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Readiness: 503 with a per-component breakdown unless every dependency is usable.
+/// 503 isn't rewritten by the error handlers, so the breakdown reaches the probe as is.
+#[routes]
 #[get("/health")]
-pub async fn health(db: web::Data<MongoDb>) -> impl Responder {
-    match db.database.run_command(
-        mongodb::bson::doc! { "ping": 1 },
-    ).await {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "status": "healthy",
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "database": "connected"
-        })),
-        Err(err) => {
-            eprintln!("Health ping failed: {}", err);
-
-            HttpResponse::Ok().json(serde_json::json!({ // !TODO, for now we can't properly handle 500 response code due to generic internal server eroor handler failure. Fix it
-                "status": "unhealthy",
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "database": "disconnected",
-                "error": err.to_string()
-            }))
+#[get("/health/ready")]
+pub async fn ready(req: HttpRequest) -> impl Responder {
+    // Looked up rather than extracted, so a missing dependency is reported instead of failing with a 500
+    let (mongodb, templates, log_writer) = futures::join!(
+        check_mongodb(req.app_data::<web::Data<MongoDb>>()),
+        async { check_templates() },
+        async { check_log_writer(req.app_data::<web::Data<LogWriter>>()) },
+    );
+
+    let is_ready = mongodb.is_up() && templates.is_up() && log_writer.is_up();
+    for (name, component) in [("mongodb", &mongodb), ("templates", &templates), ("log_writer", &log_writer)] {
+        if let Some(error) = &component.error {
+            warn!(component = name, error = %error, "Readiness check failed");
         }
     }
-}
\ No newline at end of file
+
+    let body = serde_json::json!({
+        "status": if is_ready { "ready" } else { "not_ready" },
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "components": {
+            "mongodb": mongodb,
+            "templates": templates,
+            "log_writer": log_writer,
+        }
+    });
+
+    if is_ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+async fn check_mongodb(db: Option<&web::Data<MongoDb>>) -> ComponentHealth {
+    let started = Instant::now();
+    let result = match db {
+        Some(db) => {
+            let ping = db.database.run_command(mongodb::bson::doc! { "ping": 1 });
+            match actix_web::rt::time::timeout(PING_TIMEOUT, ping).await {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err(format!("ping timed out after {}s", PING_TIMEOUT.as_secs())),
+            }
+        }
+        None => Err("not configured".to_string()),
+    };
+    ComponentHealth::from_result(result, started)
+}
+
+fn check_templates() -> ComponentHealth {
+    let started = Instant::now();
+    let missing: Vec<&str> = REQUIRED_TEMPLATES.into_iter()
+        .filter(|name| !TEMPLATES.contains(name))
+        .collect();
+    let result = if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("missing templates: {}", missing.join(", ")))
+    };
+    ComponentHealth::from_result(result, started)
+}
+
+fn check_log_writer(writer: Option<&web::Data<LogWriter>>) -> ComponentHealth {
+    let started = Instant::now();
+    let result = match writer {
+        Some(writer) => writer.check().map(|_| ()),
+        None => Err("not configured".to_string()),
+    };
+    ComponentHealth::from_result(result, started)
+}
@@ -1,13 +1,10 @@
 use actix_web::{post, web, HttpResponse, Responder, HttpRequest};
 use serde::{Deserialize, Serialize};
-use crate::logging::{request_logger::RequestLog, ClickLog, LoggerDb, RequestLogger};
-
-#[derive(Debug, Deserialize)]
-pub struct ClickLogRequest {
-    pub element: String,
-    pub page_path: String,
-    pub event_type: String,
-}
+use crate::logging::{request_logger::RequestLog, LogStore, RequestLogger};
+use crate::logging::user_agent::UserAgentInfo;
+use crate::logging::privacy;
+use crate::config::HONOR_DO_NOT_TRACK;
+use tracing::error;
 
 #[derive(Debug, Deserialize)]
 pub struct RequestLogRequest {
@@ -26,67 +23,45 @@ pub struct ApiResponse {
     pub message: String,
 }
 
-/// Log a click event
-#[post("/click")]
-pub async fn log_click(
-    req: HttpRequest,
-    body: web::Json<ClickLogRequest>,
-    logger_db: web::Data<LoggerDb>,
-) -> impl Responder {
-    let ip = RequestLogger::extract_ip(&req);
-    let user_agent = RequestLogger::extract_user_agent(&req);
-    let request_id = RequestLogger::create_request_id();
-    
-    let click_log = ClickLog {
-        id: None,
-        timestamp: mongodb::bson::DateTime::now(),
-        request_id,
-        ip_address: ip,
-        user_agent,
-        event_type: body.event_type.clone(),
-        element: body.element.clone(),
-        page_path: body.page_path.clone(),
-    };
-
-    match logger_db.log_click(click_log).await {
-        Ok(_) => HttpResponse::Ok().json(ApiResponse {
-            success: true,
-            message: "Click logged successfully".to_string(),
-        }),
-        Err(e) => {
-            eprintln!("Failed to log click: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse {
-                success: false,
-                message: "Failed to log click".to_string(),
-            })
-        }
-    }
-}
-
 /// Log a request event
 #[post("/request")]
 pub async fn log_request(
     req: HttpRequest,
     body: web::Json<RequestLogRequest>,
-    logger_db: web::Data<LoggerDb>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
-    let ip = RequestLogger::extract_ip(&req);
+    if *HONOR_DO_NOT_TRACK && privacy::has_opted_out(&req) {
+        return HttpResponse::Ok().json(ApiResponse {
+            success: true,
+            message: "Tracking disabled by Do-Not-Track, request not logged".to_string(),
+        });
+    }
+
+    let client_ip = RequestLogger::extract_client_ip(&req);
     let user_agent = RequestLogger::extract_user_agent(&req);
-    let request_id = RequestLogger::create_request_id();
+    let request_id = RequestLogger::request_id(&req).unwrap_or_else(RequestLogger::create_request_id);
     
     let request_log = RequestLog {
         id: None,
         timestamp: mongodb::bson::DateTime::now(),
         request_id,
-        ip_address: ip,
+        ip_address: privacy::anonymize_ip(&client_ip),
+        visitor_id: Some(privacy::visitor_id(&client_ip, &user_agent)),
+        user_agent_info: Some(UserAgentInfo::parse(&user_agent)),
         user_agent,
         method: body.method.clone(),
         path: body.path.clone(),
+        route: None,
         status_code: body.status_code,
         response_time_ms: body.response_time_ms,
         location: body.location.clone(),
         country: body.country.clone(),
         city: body.city.clone(),
+        referrer: None,
+        referrer_host: None,
+        utm_source: None,
+        utm_medium: None,
+        utm_campaign: None,
     };
 
     match logger_db.log_request(request_log).await {
@@ -95,7 +70,7 @@ pub async fn log_request(
             message: "Request logged successfully".to_string(),
         }),
         Err(e) => {
-            eprintln!("Failed to log request: {}", e);
+            error!(error = %e, "Failed to log request");
             HttpResponse::InternalServerError().json(ApiResponse {
                 success: false,
                 message: "Failed to log request".to_string(),
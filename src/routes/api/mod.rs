@@ -2,8 +2,9 @@ pub mod health;
 pub mod logging;
 
 use actix_web::{web, Scope};
-pub use health::health as health_handler;
+pub use health::{live as liveness_handler, ready as readiness_handler};
 pub fn api_scope() -> Scope {
     web::scope("/api")
-        .service(health_handler)
+        .service(liveness_handler)
+        .service(readiness_handler)
 }
\ No newline at end of file
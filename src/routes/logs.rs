@@ -1,13 +1,24 @@
-use actix_web::{get, post, delete, web, HttpResponse, Responder, Scope};
+use actix_web::{get, post, delete, web, HttpRequest, HttpResponse, Responder, Scope};
+use actix_web::cookie::Cookie;
+use actix_web::http::Uri;
 use serde::Deserialize;
-use crate::config::{TEMPLATES, IS_DEV};
-use crate::logging::LoggerDb;
+use chrono::NaiveDate;
+use crate::config::{TEMPLATES, IS_DEV, ALERTS, PRIVACY_MODE};
+use crate::alerts::notify;
+use crate::logging::{filter, live, privacy, LogStore};
+use crate::middlewares::auth::AdminAuth;
+use crate::middlewares::errors::AppError;
+use crate::logging::rollup::{self, DailyStats};
+use crate::logging::sessions::SessionStats;
+use crate::logging::export::{self, ExportFilter, ExportRecord};
+use futures::{Stream, StreamExt};
 use tera::Context;
+use tracing::error;
 
 /// View all request logs with pagination
 #[get("/requests")]
 pub async fn view_requests(
-    logger_db: web::Data<LoggerDb>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
     match logger_db.get_total_requests_by_day().await {
         Ok(logs) => {
@@ -18,24 +29,24 @@ pub async fn view_requests(
             match TEMPLATES.render("logs/requests.html", &context) {
                 Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
                 Err(e) => {
-                    eprintln!("Template error: {}", e);
+                    error!(error = %e, "Template error");
                     let error_message = if *IS_DEV {
                         format!("Template error: {}", e)
                     } else {
                         "Template rendering error".to_string()
                     };
-                    HttpResponse::InternalServerError().body(error_message)
+                    HttpResponse::from_error(AppError::new(error_message))
                 }
             }
         }
         Err(e) => {
-            eprintln!("Database error: {}", e);
+            error!(error = %e, "Database error");
             let error_message = if *IS_DEV {
                 format!("Database error: {}", e)
             } else {
                 "Failed to fetch logs".to_string()
             };
-            HttpResponse::InternalServerError().body(error_message)
+            HttpResponse::from_error(AppError::new(error_message))
         }
     }
 }
@@ -43,7 +54,7 @@ pub async fn view_requests(
 /// View click logs
 #[get("/clicks")]
 pub async fn view_clicks(
-    logger_db: web::Data<LoggerDb>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
     match logger_db.get_click_stats(None).await {
         Ok(stats) => {
@@ -54,65 +65,132 @@ pub async fn view_clicks(
             match TEMPLATES.render("logs/clicks.html", &context) {
                 Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
                 Err(e) => {
-                    eprintln!("Template error: {}", e);
+                    error!(error = %e, "Template error");
                     let error_message = if *IS_DEV {
                         format!("Template error: {}", e)
                     } else {
                         "Template rendering error".to_string()
                     };
-                    HttpResponse::InternalServerError().body(error_message)
+                    HttpResponse::from_error(AppError::new(error_message))
                 }
             }
         }
         Err(e) => {
-            eprintln!("Database error: {}", e);
+            error!(error = %e, "Database error");
             let error_message = if *IS_DEV {
                 format!("Database error: {}", e)
             } else {
                 "Failed to fetch click stats".to_string()
             };
-            HttpResponse::InternalServerError().body(error_message)
+            HttpResponse::from_error(AppError::new(error_message))
         }
     }
 }
 
+/// Cookie holding the "exclude bots from stats" toggle
+const EXCLUDE_BOTS_COOKIE: &str = "exclude_bots";
+
+fn excludes_bots(req: &HttpRequest) -> bool {
+    req.cookie(EXCLUDE_BOTS_COOKIE)
+        .is_some_and(|cookie| cookie.value() == "1")
+}
+
+/// Rollups for past days plus a live aggregation of today, oldest first
+async fn load_daily_stats(logger_db: &dyn LogStore, exclude_bots: bool) -> (Vec<DailyStats>, DailyStats) {
+    let mut days = logger_db.get_daily_stats(exclude_bots).await.unwrap_or_else(|e| {
+        error!(error = %e, "Failed to fetch daily stats");
+        Vec::new()
+    });
+
+    let today = chrono::Utc::now().date_naive();
+    let today_stats = match logger_db.compute_daily_stats(today, exclude_bots).await {
+        Ok(stats) => stats,
+        Err(e) => {
+            error!(error = %e, "Failed to compute today's stats");
+            DailyStats::default()
+        }
+    };
+
+    days.retain(|day| day.date != today_stats.date);
+    if !today_stats.is_empty() {
+        days.push(today_stats.clone());
+    }
+
+    (days, today_stats)
+}
+
 /// View logs dashboard
 #[get("")]
 pub async fn logs_dashboard(
-    logger_db: web::Data<LoggerDb>,
+    req: HttpRequest,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
-    // Fetch summary data
-    let requests_by_day = logger_db.get_total_requests_by_day().await.ok();
-    let clicks_by_day = logger_db.get_total_clicks_by_day().await.ok();
-    let unique_ips = logger_db.get_unique_ips().await.ok();
-    let total_requests = logger_db.get_total_request_count().await.unwrap_or(0);
-    let total_clicks = logger_db.get_total_click_count().await.unwrap_or(0);
-    
+    // Past days come from the materialized rollups, only today is aggregated live
+    let exclude_bots = excludes_bots(&req);
+    let (days, today_stats) = load_daily_stats(logger_db.get_ref(), exclude_bots).await;
+
+    let total_requests: u64 = days.iter().map(|day| day.total_requests).sum();
+    let total_clicks: u64 = days.iter().map(|day| day.total_clicks).sum();
+    let requests_by_day: Vec<&DailyStats> = days.iter().filter(|day| day.total_requests > 0).collect();
+    let clicks_by_day: Vec<&DailyStats> = days.iter().filter(|day| day.total_clicks > 0).collect();
+
     let mut context = Context::new();
     context.insert("page_title", "Logs Dashboard");
+    context.insert("exclude_bots", &exclude_bots);
     context.insert("requests_by_day", &requests_by_day);
     context.insert("clicks_by_day", &clicks_by_day);
     context.insert("total_requests", &total_requests);
     context.insert("total_clicks", &total_clicks);
-    
-    if let Some(ips) = &unique_ips {
-        context.insert("unique_ip_count", &ips.len());
-    }
+    context.insert("unique_visitors_today", &today_stats.unique_visitors);
+    context.insert("today", &today_stats);
+    context.insert("countries", &rollup::merge_breakdown(&days, |day| &day.countries));
+    context.insert("browsers", &rollup::merge_breakdown(&days, |day| &day.browsers));
+    context.insert("operating_systems", &rollup::merge_breakdown(&days, |day| &day.operating_systems));
+    context.insert("device_types", &rollup::merge_breakdown(&days, |day| &day.device_types));
+    context.insert("bots", &rollup::merge_breakdown(&days, |day| &day.bots));
+    context.insert("sessions", &SessionStats::merge(days.iter().map(|day| &day.sessions)));
     
     match TEMPLATES.render("logs/dashboard.html", &context) {
         Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
         Err(e) => {
-            eprintln!("Template error: {}", e);
-            HttpResponse::InternalServerError().body("Template rendering error")
+            error!(error = %e, "Template error");
+            HttpResponse::from_error(AppError::new("Template rendering error"))
         }
     }
 }
 
+/// Flip the bot exclusion toggle and go back to the logs page it was submitted from
+#[post("/bots/toggle")]
+pub async fn toggle_bots(req: HttpRequest) -> impl Responder {
+    let value = if excludes_bots(&req) { "0" } else { "1" };
+    let cookie = Cookie::build(EXCLUDE_BOTS_COOKIE, value)
+        .path("/logs")
+        .http_only(true)
+        .finish();
+
+    HttpResponse::SeeOther()
+        .cookie(cookie)
+        .insert_header(("Location", logs_page_from_referer(&req)))
+        .finish()
+}
+
+/// Path and query of the `Referer` when it is a logs page, so the redirect never leaves the site
+fn logs_page_from_referer(req: &HttpRequest) -> String {
+    req.headers()
+        .get("Referer")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|referer| referer.parse::<Uri>().ok())
+        .and_then(|uri| uri.path_and_query().cloned())
+        .filter(|page| page.path() == "/logs" || page.path().starts_with("/logs/"))
+        .map(|page| page.to_string())
+        .unwrap_or_else(|| "/logs/".to_string())
+}
+
 /// View logs by specific IP
 #[get("/ip/{ip}")]
 pub async fn view_logs_by_ip(
     ip: web::Path<String>,
-    logger_db: web::Data<LoggerDb>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
     let ip = ip.into_inner();
     
@@ -126,24 +204,60 @@ pub async fn view_logs_by_ip(
             match TEMPLATES.render("logs/ip_logs.html", &context) {
                 Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
                 Err(e) => {
-                    eprintln!("Template error: {}", e);
+                    error!(error = %e, "Template error");
                     let error_message = if *IS_DEV {
                         format!("Template error: {}", e)
                     } else {
                         "Template rendering error".to_string()
                     };
-                    HttpResponse::InternalServerError().body(error_message)
+                    HttpResponse::from_error(AppError::new(error_message))
                 }
             }
         }
         Err(e) => {
-            eprintln!("Database error: {}", e);
+            error!(error = %e, "Database error");
             let error_message = if *IS_DEV {
                 format!("Database error: {}", e)
             } else {
                 "Failed to fetch logs".to_string()
             };
-            HttpResponse::InternalServerError().body(error_message)
+            HttpResponse::from_error(AppError::new(error_message))
+        }
+    }
+}
+
+/// Erase all request and click logs for an IP (GDPR erasure request).
+/// Only possible while full IPs are stored, see `privacy::erasure_blocked_by`.
+#[delete("/ip/{ip}")]
+pub async fn erase_logs_by_ip(
+    _admin: AdminAuth,
+    ip: web::Path<String>,
+    logger_db: web::Data<dyn LogStore>,
+) -> impl Responder {
+    let ip = ip.into_inner();
+
+    if let Some(reason) = privacy::erasure_blocked_by(*PRIVACY_MODE) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "success": false,
+            "message": reason
+        }));
+    }
+
+    match logger_db.erase_ip(&ip).await {
+        Ok((requests, clicks)) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "success": true,
+                "deleted_requests": requests,
+                "deleted_clicks": clicks,
+                "message": format!("Erased {} requests and {} clicks for {}", requests, clicks, ip)
+            }))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to erase logs");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "message": "Failed to erase logs"
+            }))
         }
     }
 }
@@ -151,12 +265,12 @@ pub async fn view_logs_by_ip(
 /// View logs for a specific date
 #[get("/date/{date}")]
 pub async fn view_logs_by_date(
-    date: web::Path<String>,
-    logger_db: web::Data<LoggerDb>,
+    date: web::Path<NaiveDate>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
     let date = date.into_inner();
     
-    match logger_db.get_request_stats_by_date(&date).await {
+    match logger_db.get_request_stats_by_date(date).await {
         Ok(stats) => {
             let mut context = Context::new();
             context.insert("stats", &stats);
@@ -166,49 +280,61 @@ pub async fn view_logs_by_date(
             match TEMPLATES.render("logs/date_stats.html", &context) {
                 Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
                 Err(e) => {
-                    eprintln!("Template error: {}", e);
+                    error!(error = %e, "Template error");
                     let error_message = if *IS_DEV {
                         format!("Template error: {}", e)
                     } else {
                         "Template rendering error".to_string()
                     };
-                    HttpResponse::InternalServerError().body(error_message)
+                    HttpResponse::from_error(AppError::new(error_message))
                 }
             }
         }
         Err(e) => {
-            eprintln!("Database error: {}", e);
+            error!(error = %e, "Database error");
             let error_message = if *IS_DEV {
                 format!("Database error: {}", e)
             } else {
                 "Failed to fetch stats".to_string()
             };
-            HttpResponse::InternalServerError().body(error_message)
+            HttpResponse::from_error(AppError::new(error_message))
         }
     }
 }
 
-/// View all individual requests with pagination and search
+/// View all individual requests with pagination and search.
+/// `search` accepts the filter syntax from `filter::parse_request_filter`.
 #[get("/requests/all")]
 pub async fn view_all_requests(
-    logger_db: web::Data<LoggerDb>,
+    logger_db: web::Data<dyn LogStore>,
     query: web::Query<RequestsQuery>,
 ) -> impl Responder {
-    let search_query = query.search.as_deref();
-    let page = query.page.unwrap_or(1);
+    let search_query = query.search.as_deref().map(str::trim).filter(|search| !search.is_empty());
+    let page = query.page.unwrap_or(1).max(1);
     let per_page = 50;
-    let skip = ((page - 1) * per_page) as u64;
-    
-    let logs_result = if let Some(search) = search_query {
-        logger_db.search_requests(search).await
-    } else {
-        logger_db.get_all_requests(Some(per_page as i64), Some(skip)).await
+    let skip = (page - 1) * per_page;
+
+    let (filter, filter_error) = match search_query.map(filter::parse_request_filter) {
+        Some(Ok(filter)) => (Some(filter), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+
+    let logs_result = match (&filter, &filter_error) {
+        (Some(filter), _) => match logger_db.search_requests(filter, per_page as i64, skip).await {
+            Ok(logs) => logger_db.count_matching_requests(filter).await.map(|count| (logs, count)),
+            Err(e) => Err(e),
+        },
+        (None, Some(_)) => Ok((Vec::new(), 0)),
+        (None, None) => match logger_db.get_all_requests(Some(per_page as i64), Some(skip)).await {
+            Ok(logs) => Ok((logs, logger_db.get_total_request_count().await.unwrap_or(0))),
+            Err(e) => Err(e),
+        },
     };
     
     match logs_result {
-        Ok(logs) => {
-            let total_count = logger_db.get_total_request_count().await.unwrap_or(0);
-            let total_pages = (total_count as f64 / per_page as f64).ceil() as u64;
+        Ok((logs, total_count)) => {
+            let total_pages = total_count.div_ceil(per_page);
             
             let mut context = Context::new();
             context.insert("logs", &logs);
@@ -217,28 +343,29 @@ pub async fn view_all_requests(
             context.insert("total_pages", &total_pages);
             context.insert("total_count", &total_count);
             context.insert("search_query", &search_query);
+            context.insert("filter_error", &filter_error);
             
             match TEMPLATES.render("logs/all_requests.html", &context) {
                 Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
                 Err(e) => {
-                    eprintln!("Template error: {}", e);
+                    error!(error = %e, "Template error");
                     let error_message = if *IS_DEV {
                         format!("Template error: {}", e)
                     } else {
                         "Template rendering error".to_string()
                     };
-                    HttpResponse::InternalServerError().body(error_message)
+                    HttpResponse::from_error(AppError::new(error_message))
                 }
             }
         }
         Err(e) => {
-            eprintln!("Database error: {}", e);
+            error!(error = %e, "Database error");
             let error_message = if *IS_DEV {
                 format!("Database error: {}", e)
             } else {
                 "Failed to fetch logs".to_string()
             };
-            HttpResponse::InternalServerError().body(error_message)
+            HttpResponse::from_error(AppError::new(error_message))
         }
     }
 }
@@ -252,12 +379,12 @@ pub struct RequestsQuery {
 /// Delete logs by date
 #[delete("/date/{date}")]
 pub async fn delete_logs_by_date(
-    date: web::Path<String>,
-    logger_db: web::Data<LoggerDb>,
+    date: web::Path<NaiveDate>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
     let date = date.into_inner();
     
-    match logger_db.delete_requests_by_date(&date).await {
+    match logger_db.delete_requests_by_date(date).await {
         Ok(deleted_count) => {
             HttpResponse::Ok().json(serde_json::json!({
                 "success": true,
@@ -266,7 +393,7 @@ pub async fn delete_logs_by_date(
             }))
         }
         Err(e) => {
-            eprintln!("Failed to delete logs: {}", e);
+            error!(error = %e, "Failed to delete logs");
             HttpResponse::InternalServerError().json(serde_json::json!({
                 "success": false,
                 "message": "Failed to delete logs"
@@ -278,12 +405,12 @@ pub async fn delete_logs_by_date(
 /// View detailed logs for a specific date
 #[get("/date/{date}/details")]
 pub async fn view_date_details(
-    date: web::Path<String>,
-    logger_db: web::Data<LoggerDb>,
+    date: web::Path<NaiveDate>,
+    logger_db: web::Data<dyn LogStore>,
 ) -> impl Responder {
     let date = date.into_inner();
     
-    match logger_db.get_requests_by_date(&date).await {
+    match logger_db.get_requests_by_date(date).await {
         Ok(logs) => {
             let mut context = Context::new();
             context.insert("logs", &logs);
@@ -294,24 +421,461 @@ pub async fn view_date_details(
             match TEMPLATES.render("logs/date_details.html", &context) {
                 Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
                 Err(e) => {
-                    eprintln!("Template error: {}", e);
+                    error!(error = %e, "Template error");
                     let error_message = if *IS_DEV {
                         format!("Template error: {}", e)
                     } else {
                         "Template rendering error".to_string()
                     };
-                    HttpResponse::InternalServerError().body(error_message)
+                    HttpResponse::from_error(AppError::new(error_message))
                 }
             }
         }
         Err(e) => {
-            eprintln!("Database error: {}", e);
+            error!(error = %e, "Database error");
             let error_message = if *IS_DEV {
                 format!("Database error: {}", e)
             } else {
                 "Failed to fetch logs".to_string()
             };
-            HttpResponse::InternalServerError().body(error_message)
+            HttpResponse::from_error(AppError::new(error_message))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DaysQuery {
+    pub days: Option<i64>,
+}
+
+/// View latency percentiles by day and by route, plus the slowest requests
+#[get("/performance")]
+pub async fn view_performance(
+    req: HttpRequest,
+    logger_db: web::Data<dyn LogStore>,
+    query: web::Query<DaysQuery>,
+) -> impl Responder {
+    let days = query.days.unwrap_or(7).clamp(1, 90);
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    let exclude_bots = excludes_bots(&req);
+
+    let routes = match logger_db.get_route_performance(since, exclude_bots).await {
+        Ok(routes) => routes,
+        Err(e) => {
+            error!(error = %e, "Database error");
+            let error_message = if *IS_DEV {
+                format!("Database error: {}", e)
+            } else {
+                "Failed to fetch route performance".to_string()
+            };
+            return HttpResponse::from_error(AppError::new(error_message));
+        }
+    };
+
+    let slowest_requests = logger_db.get_slowest_requests(since, exclude_bots, 50).await.unwrap_or_else(|e| {
+        error!(error = %e, "Failed to fetch slowest requests");
+        Vec::new()
+    });
+
+    let (mut daily, _) = load_daily_stats(logger_db.get_ref(), exclude_bots).await;
+    daily.retain(|day| day.total_requests > 0);
+    daily.reverse();
+
+    let mut context = Context::new();
+    context.insert("page_title", "Performance");
+    context.insert("exclude_bots", &exclude_bots);
+    context.insert("days", &days);
+    context.insert("routes", &routes);
+    context.insert("slowest_requests", &slowest_requests);
+    context.insert("daily", &daily);
+
+    match TEMPLATES.render("logs/performance.html", &context) {
+        Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
+        Err(e) => {
+            error!(error = %e, "Template error");
+            let error_message = if *IS_DEV {
+                format!("Template error: {}", e)
+            } else {
+                "Template rendering error".to_string()
+            };
+            HttpResponse::from_error(AppError::new(error_message))
+        }
+    }
+}
+
+/// Session metrics per day and for the whole range, as JSON
+#[get("/sessions")]
+pub async fn sessions_json(
+    req: HttpRequest,
+    logger_db: web::Data<dyn LogStore>,
+    query: web::Query<DaysQuery>,
+) -> impl Responder {
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let exclude_bots = excludes_bots(&req);
+    let first_day = (chrono::Utc::now() - chrono::Duration::days(days - 1)).format("%Y-%m-%d").to_string();
+
+    let (mut daily, _) = load_daily_stats(logger_db.get_ref(), exclude_bots).await;
+    daily.retain(|day| day.date >= first_day);
+
+    let per_day: Vec<serde_json::Value> = daily
+        .iter()
+        .map(|day| serde_json::json!({ "date": day.date, "sessions": day.sessions }))
+        .collect();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "days": days,
+        "exclude_bots": exclude_bots,
+        "total": SessionStats::merge(daily.iter().map(|day| &day.sessions)),
+        "daily": per_day,
+    }))
+}
+
+/// Live traffic page, fed by `/logs/live/stream`
+#[get("/live")]
+pub async fn view_live() -> impl Responder {
+    let mut context = Context::new();
+    context.insert("page_title", "Live Traffic");
+    context.insert("snapshot", &live::snapshot());
+
+    match TEMPLATES.render("logs/live.html", &context) {
+        Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
+        Err(e) => {
+            error!(error = %e, "Template error");
+            let error_message = if *IS_DEV {
+                format!("Template error: {}", e)
+            } else {
+                "Template rendering error".to_string()
+            };
+            HttpResponse::from_error(AppError::new(error_message))
+        }
+    }
+}
+
+/// Server-Sent Events: a `request` event per logged request and a `snapshot` event every second
+#[get("/live/stream")]
+pub async fn live_stream() -> impl Responder {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let requests = futures::stream::unfold(live::subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(request) => return Some((sse_event("request", &request), receiver)),
+                // A slow viewer just misses some rows
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let ticker = actix_web::rt::time::interval(std::time::Duration::from_secs(1));
+    let snapshots = futures::stream::unfold(ticker, |mut ticker| async move {
+        ticker.tick().await;
+        Some((sse_event("snapshot", &live::snapshot()), ticker))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        // Compress would buffer the events, so opt this response out of it
+        .insert_header(actix_web::http::header::ContentEncoding::Identity)
+        .streaming(futures::stream::select(requests, snapshots))
+}
+
+fn sse_event(name: &str, data: &impl serde::Serialize) -> Result<web::Bytes, actix_web::Error> {
+    let json = serde_json::to_string(data)?;
+    Ok(web::Bytes::from(format!("event: {}\ndata: {}\n\n", name, json)))
+}
+
+/// Stream request or click logs as CSV or NDJSON: `/logs/export/{requests,clicks}.{csv,ndjson}`
+#[get("/export/{file}")]
+pub async fn export_logs(
+    file: web::Path<String>,
+    logger_db: web::Data<dyn LogStore>,
+    query: web::Query<ExportFilter>,
+) -> impl Responder {
+    let file = file.into_inner();
+    let Some((kind, format)) = file.split_once('.') else {
+        return HttpResponse::NotFound().body("Unknown export");
+    };
+    if format != "csv" && format != "ndjson" {
+        return HttpResponse::NotFound().body("Unknown export format");
+    }
+
+    let result = match kind {
+        "requests" => logger_db.export_requests(&query).await.map(|cursor| export_response(&file, format, cursor)),
+        "clicks" => logger_db.export_clicks(&query).await.map(|cursor| export_response(&file, format, cursor)),
+        _ => return HttpResponse::NotFound().body("Unknown export"),
+    };
+
+    result.unwrap_or_else(|e| {
+        error!(error = %e, "Database error");
+        let error_message = if *IS_DEV {
+            format!("Database error: {}", e)
+        } else {
+            "Failed to export logs".to_string()
+        };
+        HttpResponse::from_error(AppError::new(error_message))
+    })
+}
+
+/// Encode each record as it comes off the cursor, so the export is never held in memory
+fn export_response<T, S>(file: &str, format: &str, cursor: S) -> HttpResponse
+where
+    T: ExportRecord + 'static,
+    S: Stream<Item = Result<T, Box<dyn std::error::Error>>> + 'static,
+{
+    let csv = format == "csv";
+    let header = csv.then(|| export::csv_line(T::CSV_HEADER));
+
+    let rows = cursor.map(move |record| {
+        let record = record.map_err(actix_web::error::ErrorInternalServerError)?;
+        let line = if csv { export::csv_line(&record.csv_row()) } else { export::ndjson_line(&record) };
+        line.map(web::Bytes::from).map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))
+    });
+    let body = futures::stream::iter(header)
+        .map(|line| line.map(web::Bytes::from).map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string())))
+        .chain(rows);
+
+    HttpResponse::Ok()
+        .content_type(if csv { "text/csv; charset=utf-8" } else { "application/x-ndjson" })
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", file)))
+        .streaming(body)
+}
+
+#[derive(Deserialize)]
+pub struct ErrorsQuery {
+    pub show: Option<String>,
+}
+
+/// Browse tracked error groups; resolved groups are hidden unless `show=all`
+#[get("/errors")]
+pub async fn view_errors(
+    logger_db: web::Data<dyn LogStore>,
+    query: web::Query<ErrorsQuery>,
+) -> impl Responder {
+    let show_all = query.show.as_deref() == Some("all");
+
+    match logger_db.get_error_groups(show_all).await {
+        Ok(groups) => {
+            let format_time = |time: mongodb::bson::DateTime| {
+                chrono::DateTime::from_timestamp_millis(time.timestamp_millis())
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                    .unwrap_or_default()
+            };
+            // Flatten BSON dates so the template can print them
+            let groups: Vec<serde_json::Value> = groups
+                .iter()
+                .map(|group| serde_json::json!({
+                    "fingerprint": group.fingerprint,
+                    "status_code": group.status_code,
+                    "method": group.method,
+                    "route": group.route,
+                    "message": group.message,
+                    "count": group.count,
+                    "first_seen": format_time(group.first_seen),
+                    "last_seen": format_time(group.last_seen),
+                    "last_path": group.last_path,
+                    "recent_request_ids": group.recent_request_ids,
+                    "details": group.details,
+                    "resolved": group.resolved,
+                }))
+                .collect();
+
+            let mut context = Context::new();
+            context.insert("page_title", "Errors");
+            context.insert("show_all", &show_all);
+            context.insert("groups", &groups);
+
+            match TEMPLATES.render("logs/errors.html", &context) {
+                Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
+                Err(e) => {
+                    error!(error = %e, "Template error");
+                    let error_message = if *IS_DEV {
+                        format!("Template error: {}", e)
+                    } else {
+                        "Template rendering error".to_string()
+                    };
+                    HttpResponse::from_error(AppError::new(error_message))
+                }
+            }
+        }
+        Err(e) => {
+            error!(error = %e, "Database error");
+            let error_message = if *IS_DEV {
+                format!("Database error: {}", e)
+            } else {
+                "Failed to fetch errors".to_string()
+            };
+            HttpResponse::from_error(AppError::new(error_message))
+        }
+    }
+}
+
+/// Mark an error group resolved (`resolve`) or open it again (`reopen`)
+#[post("/errors/{fingerprint}/{action}")]
+pub async fn update_error_group(
+    path: web::Path<(String, String)>,
+    logger_db: web::Data<dyn LogStore>,
+) -> impl Responder {
+    let (fingerprint, action) = path.into_inner();
+    let resolved = match action.as_str() {
+        "resolve" => true,
+        "reopen" => false,
+        _ => return HttpResponse::NotFound().json(serde_json::json!({
+            "success": false,
+            "message": format!("Unknown action '{}'", action)
+        })),
+    };
+
+    match logger_db.set_error_resolved(&fingerprint, resolved).await {
+        Ok(true) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "resolved": resolved
+        })),
+        Ok(false) => HttpResponse::NotFound().json(serde_json::json!({
+            "success": false,
+            "message": "Error group not found"
+        })),
+        Err(e) => {
+            error!(error = %e, "Database error");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "message": "Failed to update error group"
+            }))
+        }
+    }
+}
+
+/// Send a test notification through every configured alert channel
+#[post("/alerts/test")]
+pub async fn test_alerts(_admin: AdminAuth) -> impl Responder {
+    if !ALERTS.is_enabled() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "message": "No alert channel configured, set ALERT_WEBHOOK_URL or SMTP_HOST"
+        }));
+    }
+
+    let errors = notify::send(&ALERTS, &notify::Notification::test()).await;
+    if errors.is_empty() {
+        HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "message": "Test notification sent"
+        }))
+    } else {
+        HttpResponse::BadGateway().json(serde_json::json!({
+            "success": false,
+            "message": errors.join("; ")
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SystemQuery {
+    pub hours: Option<i64>,
+}
+
+/// View process memory, CPU, connections and in-flight requests over time
+#[get("/system")]
+pub async fn view_system(
+    logger_db: web::Data<dyn LogStore>,
+    query: web::Query<SystemQuery>,
+) -> impl Responder {
+    let hours = query.hours.unwrap_or(24).clamp(1, 24 * 30);
+    let since = chrono::Utc::now() - chrono::Duration::hours(hours);
+
+    match logger_db.get_performance_logs(since).await {
+        Ok(samples) => {
+            // Flatten to plain JSON so the chart script doesn't have to unpack BSON dates
+            let points: Vec<serde_json::Value> = samples
+                .iter()
+                .map(|sample| serde_json::json!({
+                    "timestamp": sample.timestamp.timestamp_millis(),
+                    "memory_usage_mb": sample.memory_usage_mb,
+                    "cpu_usage_percent": sample.cpu_usage_percent,
+                    "active_connections": sample.active_connections,
+                    "in_flight_requests": sample.request_queue_depth,
+                    "render_time_ms": sample.render_time_ms,
+                }))
+                .collect();
+
+            let mut context = Context::new();
+            context.insert("page_title", "System Performance");
+            context.insert("hours", &hours);
+            context.insert("points", &points);
+            context.insert("latest", &samples.last());
+
+            match TEMPLATES.render("logs/system.html", &context) {
+                Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
+                Err(e) => {
+                    error!(error = %e, "Template error");
+                    let error_message = if *IS_DEV {
+                        format!("Template error: {}", e)
+                    } else {
+                        "Template rendering error".to_string()
+                    };
+                    HttpResponse::from_error(AppError::new(error_message))
+                }
+            }
+        }
+        Err(e) => {
+            error!(error = %e, "Database error");
+            let error_message = if *IS_DEV {
+                format!("Database error: {}", e)
+            } else {
+                "Failed to fetch performance logs".to_string()
+            };
+            HttpResponse::from_error(AppError::new(error_message))
+        }
+    }
+}
+
+/// View top external referrers and UTM campaigns
+#[get("/referrers")]
+pub async fn view_referrers(
+    req: HttpRequest,
+    logger_db: web::Data<dyn LogStore>,
+    query: web::Query<DaysQuery>,
+) -> impl Responder {
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    let exclude_bots = excludes_bots(&req);
+
+    let referrers = match logger_db.get_top_referrers(since, exclude_bots, 50).await {
+        Ok(referrers) => referrers,
+        Err(e) => {
+            error!(error = %e, "Database error");
+            let error_message = if *IS_DEV {
+                format!("Database error: {}", e)
+            } else {
+                "Failed to fetch referrers".to_string()
+            };
+            return HttpResponse::from_error(AppError::new(error_message));
+        }
+    };
+
+    let campaigns = logger_db.get_campaigns(since, exclude_bots).await.unwrap_or_else(|e| {
+        error!(error = %e, "Failed to fetch campaigns");
+        Vec::new()
+    });
+
+    let mut context = Context::new();
+    context.insert("page_title", "Referrers & Campaigns");
+    context.insert("exclude_bots", &exclude_bots);
+    context.insert("days", &days);
+    context.insert("referrers", &referrers);
+    context.insert("campaigns", &campaigns);
+
+    match TEMPLATES.render("logs/referrers.html", &context) {
+        Ok(html) => HttpResponse::Ok().content_type("text/html").body(html),
+        Err(e) => {
+            error!(error = %e, "Template error");
+            let error_message = if *IS_DEV {
+                format!("Template error: {}", e)
+            } else {
+                "Template rendering error".to_string()
+            };
+            HttpResponse::from_error(AppError::new(error_message))
         }
     }
 }
@@ -324,7 +888,19 @@ pub fn logs_scope() -> Scope {
         .service(view_requests)
         .service(view_all_requests)
         .service(view_clicks)
+        .service(view_performance)
+        .service(view_system)
+        .service(toggle_bots)
+        .service(view_referrers)
+        .service(sessions_json)
+        .service(view_live)
+        .service(live_stream)
+        .service(export_logs)
+        .service(view_errors)
+        .service(update_error_group)
+        .service(test_alerts)
         .service(view_logs_by_ip)
+        .service(erase_logs_by_ip)
         .service(view_logs_by_date)
         .service(view_date_details)
         .service(delete_logs_by_date)
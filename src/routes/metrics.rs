@@ -0,0 +1,31 @@
+use actix_web::{get, HttpRequest, HttpResponse, Responder};
+use crate::config::{METRICS_TOKEN, METRICS_PORT};
+use crate::logging::metrics;
+use crate::middlewares::auth::has_bearer_token;
+use tracing::error;
+
+/// Prometheus scrape endpoint
+#[get("/metrics")]
+pub async fn metrics_handler(req: HttpRequest) -> impl Responder {
+    match METRICS_TOKEN.as_deref() {
+        Some(token) if !has_bearer_token(&req, token) => {
+            return HttpResponse::Unauthorized()
+                .insert_header(("WWW-Authenticate", "Bearer"))
+                .finish();
+        }
+        Some(_) => {}
+        // Without a token the endpoint is only exposed on the separate admin port
+        None if METRICS_PORT.is_none() => return HttpResponse::NotFound().finish(),
+        None => {}
+    }
+
+    match metrics::render() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4; charset=utf-8")
+            .body(body),
+        Err(e) => {
+            error!(error = %e, "Failed to encode metrics");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
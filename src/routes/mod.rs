@@ -1,7 +1,12 @@
 pub mod pages;
 pub mod api;
 pub mod logs;
+pub mod metrics;
 
 pub use pages::pages_scope;
 pub use api::api_scope;
-pub use logs::logs_scope;
\ No newline at end of file
+pub use logs::logs_scope;
+pub use metrics::metrics_handler;
+
+#[cfg(test)]
+mod tests;
@@ -1,25 +1,26 @@
 
-use actix_web::{error, get, web, HttpResponse, Result, Scope};
+use actix_web::{get, web, HttpResponse, Result, Scope};
 use tera::Context;
-use crate::{db, errors, };
-use crate::db::MongoDb;
+use crate::{errors, };
+use crate::db::PostRepository;
 use crate::types::PostStatus;
-use crate::errors::{internal_server_error_handler, not_found_handler};
+use crate::errors::{internal_server_error_handler, not_found_handler, AppError};
 use crate::TEMPLATES;
+use tracing::error;
 
 // -------------------- Handlers --------------------
 
 #[get("/")]
-pub async fn index(db: web::Data<MongoDb>) -> Result<HttpResponse> {
+pub async fn index(posts: web::Data<dyn PostRepository>) -> Result<HttpResponse> {
     let mut ctx = Context::new();
     ctx.insert("name", "Wizards Portfolio");
     ctx.insert("title", "Home");
 
     // Fetch latest 5 published posts
-    let posts = match db::posts::list_posts(&db.database, Some(PostStatus::Published), 5, 0).await {
+    let posts = match posts.list_posts(Some(PostStatus::Published), 5, 0).await {
         Ok(list) => list,
         Err(e) => {
-            eprintln!("Error fetching posts: {}", e);
+            error!(error = %e, "Error fetching posts");
             Vec::new()
         }
     };
@@ -28,8 +29,8 @@ pub async fn index(db: web::Data<MongoDb>) -> Result<HttpResponse> {
 
     let rendered = TEMPLATES.render("index.html", &ctx)
         .map_err(|e| {
-            eprintln!("Template rendering error: {}", e);
-            error::ErrorInternalServerError("Template rendering failed")
+            error!(error = %e, "Template rendering error");
+            AppError::new("Template rendering failed")
         })?;
 
     Ok(HttpResponse::Ok()
@@ -44,8 +45,8 @@ pub async fn about() -> Result<HttpResponse> {
 
     let rendered = TEMPLATES.render("about.html", &ctx)
         .map_err(|e| {
-            eprintln!("Template rendering error: {}", e);
-            error::ErrorInternalServerError("Template rendering failed")
+            error!(error = %e, "Template rendering error");
+            AppError::new("Template rendering failed")
         })?;
 
     Ok(HttpResponse::Ok()
@@ -0,0 +1,192 @@
+// src/routes/tests.rs
+//! Route tests against the in-memory repositories and log store, through the same middleware
+//! stack as the server.
+use std::sync::Arc;
+use std::time::Duration;
+use actix_web::http::StatusCode;
+use actix_web::middleware::{ErrorHandlers, NormalizePath};
+use actix_web::{test, web, App};
+use mongodb::bson::oid::ObjectId;
+use crate::db::repository::{MemoryPostRepository, MemoryUserRepository};
+use crate::db::{PostRepository, UserRepository};
+use crate::errors::{internal_server_error_handler, not_found_handler};
+use crate::logging::store::{LogStore, MemoryLogStore};
+use crate::logging::writer::LogWriter;
+use crate::middlewares::request_logging::RequestLogging;
+use crate::types::{Post, PostStatus};
+use super::{api_scope, logs_scope, pages_scope};
+
+struct Backends {
+    posts: Arc<dyn PostRepository>,
+    users: Arc<dyn UserRepository>,
+    log_store: Arc<dyn LogStore>,
+    log_writer: LogWriter,
+}
+
+impl Backends {
+    /// Must be called on the test's runtime, which runs the log writer task
+    fn new() -> Self {
+        let log_store: Arc<dyn LogStore> = Arc::new(MemoryLogStore::new());
+        let (log_writer, _) = LogWriter::spawn(Arc::clone(&log_store));
+        Backends {
+            posts: Arc::new(MemoryPostRepository::new()),
+            users: Arc::new(MemoryUserRepository::new()),
+            log_store,
+            log_writer,
+        }
+    }
+}
+
+macro_rules! init_app {
+    ($backends:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($backends.log_writer.clone()))
+                .app_data(web::Data::from($backends.log_store.clone()))
+                .app_data(web::Data::from($backends.posts.clone()))
+                .app_data(web::Data::from($backends.users.clone()))
+                .service(api_scope())
+                .service(logs_scope())
+                .service(pages_scope())
+                .wrap(RequestLogging)
+                .wrap(NormalizePath::trim())
+                .wrap(
+                    ErrorHandlers::new()
+                        .handler(StatusCode::INTERNAL_SERVER_ERROR, internal_server_error_handler)
+                        .handler(StatusCode::NOT_FOUND, not_found_handler),
+                ),
+        )
+        .await
+    };
+}
+
+/// Request logs are written by the log writer task after the response
+async fn wait_for_requests(log_store: &dyn LogStore, count: u64) {
+    for _ in 0..100 {
+        if log_store.get_total_request_count().await.unwrap() >= count {
+            return;
+        }
+        actix_web::rt::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected {} logged requests", count);
+}
+
+#[actix_web::test]
+async fn test_home_page() {
+    let backends = Backends::new();
+    let now = mongodb::bson::DateTime::now();
+    let post = Post {
+        id: None,
+        title: "Hello World".to_string(),
+        slug: "hello-world".to_string(),
+        content: "Content".to_string(),
+        excerpt: None,
+        status: PostStatus::Published,
+        author_id: ObjectId::new(),
+        created_at: now,
+        updated_at: now,
+        published_at: Some(now),
+        tags: Vec::new(),
+        views: 0,
+    };
+    backends.posts.save_post(&post).await.unwrap();
+    let app = init_app!(backends);
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().contains_key("x-request-id"));
+    let body = test::read_body(res).await;
+    assert!(String::from_utf8_lossy(&body).contains("Welcome to Wizards Portfolio"));
+}
+
+#[actix_web::test]
+async fn test_unknown_path_is_tracked() {
+    let backends = Backends::new();
+    let app = init_app!(backends);
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/no-such-page").to_request()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let request_id = res.headers().get("x-request-id").unwrap().to_str().unwrap().to_string();
+    let body = test::read_body(res).await;
+    assert!(String::from_utf8_lossy(&body).contains(&request_id));
+
+    wait_for_requests(&*backends.log_store, 1).await;
+    let groups = backends.log_store.get_error_groups(false).await.unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].status_code, 404);
+}
+
+#[actix_web::test]
+async fn test_logged_requests_are_listed() {
+    let backends = Backends::new();
+    let app = init_app!(backends);
+
+    for uri in ["/", "/?utm_source=newsletter", "/missing"] {
+        test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    }
+    wait_for_requests(&*backends.log_store, 3).await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/logs/requests/all").to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = test::read_body(res).await;
+    assert!(String::from_utf8_lossy(&body).contains("&#x2F;missing"));
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/logs/").to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_readiness_reports_each_component() {
+    let backends = Backends::new();
+    let app = init_app!(backends);
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/api/health/live").to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // No MongoDB in the test app, so readiness fails with its own breakdown rather than an error page
+    let res = test::call_service(&app, test::TestRequest::get().uri("/api/health/ready").to_request()).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["components"]["mongodb"]["status"], "down");
+    assert_eq!(body["components"]["templates"]["status"], "up");
+    assert_eq!(body["components"]["log_writer"]["status"], "up");
+    assert!(body["components"]["log_writer"]["latency_ms"].is_number());
+}
+
+#[actix_web::test]
+async fn test_admin_endpoints_need_a_token() {
+    let backends = Backends::new();
+    let app = init_app!(backends);
+
+    // No ADMIN_TOKEN in the test configuration, so they stay disabled rather than open
+    for req in [
+        test::TestRequest::delete().uri("/logs/ip/203.0.113.42"),
+        test::TestRequest::post().uri("/logs/alerts/test"),
+    ] {
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+#[actix_web::test]
+async fn test_bot_toggle_redirects_within_the_logs() {
+    let backends = Backends::new();
+    let app = init_app!(backends);
+
+    for (referer, location) in [
+        ("https://example.com/logs/referrers?days=30", "/logs/referrers?days=30"),
+        ("https://evil.example/logs", "/logs"),
+        ("https://evil.example/phish?next=/logs", "/logs/"),
+        ("not a url", "/logs/"),
+    ] {
+        let req = test::TestRequest::post().uri("/logs/bots/toggle").insert_header(("Referer", referer));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get("Location").unwrap(), location);
+    }
+
+    // Changing the preference takes a form submission, not a link
+    let res = test::call_service(&app, test::TestRequest::get().uri("/logs/bots/toggle").to_request()).await;
+    assert_ne!(res.status(), StatusCode::SEE_OTHER);
+}